@@ -13,7 +13,9 @@ pub const LEPTOS_OUTPUT_NAME: &str = env!("LEPTOS_OUTPUT_NAME");
 pub enum AppRoutes {
     Home,
     Draw,
+    DrawComparison,
     Calculator,
+    CalculatorReport,
     Chat,
 }
 
@@ -22,7 +24,9 @@ impl AppRoutes {
         match self {
             Self::Home => "/",
             Self::Draw => "/draw",
+            Self::DrawComparison => "/draw/:comparison_id",
             Self::Calculator => "/calculator",
+            Self::CalculatorReport => "/calculator/report",
             Self::Chat => "/chat",
         }
     }
@@ -127,7 +131,12 @@ pub fn App() -> impl IntoView {
                         <Route path="/" view=|| view! { <SideLayout/> }>
                             <Route path=AppRoutes::Home view=HomePage/>
                             <Route path=AppRoutes::Draw view=DrawPage/>
+                            <Route path=AppRoutes::DrawComparison view=DrawPage/>
                             <Route path=AppRoutes::Calculator view=CalculatorPage/>
+                            <Route
+                                path=AppRoutes::CalculatorReport
+                                view=CalculatorReportPage
+                            />
                             <Route path=AppRoutes::Chat view=ChatPage/>
                         </Route>
                     </Routes>
@@ -1,3 +1,5 @@
+mod download_button;
 mod page_title;
 
+pub use download_button::{download, download_url, DownloadButton};
 pub use page_title::PageTitle;
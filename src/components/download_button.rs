@@ -0,0 +1,62 @@
+use leptos::*;
+use wasm_bindgen::prelude::*;
+
+/// Saves `content` as a file through a temporary object URL.
+pub fn download(file_name: &str, mime: &str, content: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(content));
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_(mime);
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)?;
+
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+    download_url(file_name, &url)?;
+    // some browsers cancel the download when the url is revoked right after the click
+    set_timeout(
+        move || {
+            let _ = web_sys::Url::revoke_object_url(&url);
+        },
+        std::time::Duration::from_secs(1),
+    );
+    Ok(())
+}
+
+/// Saves what `url` points to, such as an object or data URL, as a file.
+pub fn download_url(file_name: &str, url: &str) -> Result<(), JsValue> {
+    let anchor = document()
+        .create_element("a")?
+        .unchecked_into::<web_sys::HtmlAnchorElement>();
+    anchor.set_href(url);
+    anchor.set_download(file_name);
+    anchor.click();
+    Ok(())
+}
+
+/// A button that generates a file on click and downloads it.
+#[component]
+pub fn DownloadButton(
+    #[prop(into)] file_name: MaybeSignal<String>,
+    mime: &'static str,
+    #[prop(into)] content: Callback<(), String>,
+    #[prop(into, optional)] disabled: MaybeSignal<bool>,
+    children: Children,
+) -> impl IntoView {
+    let on_click = move |_| {
+        if let Err(err) = download(&file_name.get_untracked(), mime, &content.call(())) {
+            logging::error!(
+                "Failed to download {}: {:?}",
+                file_name.get_untracked(),
+                err
+            );
+        }
+    };
+
+    view! {
+        <button
+            on:click=on_click
+            disabled=move || disabled.get()
+            class="hover:bg-cyan-600 disabled:opacity-50 disabled:cursor-not-allowed rounded-md bg-red-400 text-white text-m font-medium pl-2 pr-3 py-2 shadow-sm"
+        >
+            {children()}
+        </button>
+    }
+}
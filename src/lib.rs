@@ -3,6 +3,7 @@ pub mod components;
 #[cfg(feature = "ssr")]
 pub mod fileserv;
 pub mod layouts;
+pub mod loss;
 pub mod pages;
 
 #[cfg(feature = "hydrate")]
@@ -0,0 +1,474 @@
+//! Loss curves of training runs and the statistics of their differences, shared by the draw
+//! page and the `compare` command.
+
+pub mod compare;
+pub mod downsample;
+pub mod events;
+pub mod export;
+pub mod format;
+pub mod json;
+pub mod log;
+pub mod run;
+pub mod scale;
+pub mod segments;
+pub mod smoothing;
+pub mod table;
+pub mod tfevents;
+pub mod tolerance;
+
+use run::SeriesInfo;
+use serde::{Deserialize, Serialize};
+use table::{BadRowPolicy, ColumnMapping, RowError, Table};
+
+/// One value per plotted series at a step.
+///
+/// A series has no value at steps it did not log or where its cell was bad, NaN and infinite
+/// losses are kept as logged.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Loss {
+    pub step: f64,
+    pub values: Vec<Option<f64>>,
+    /// Smoothed `values`, empty until smoothing has run.
+    pub smoothed: Vec<Option<f64>>,
+}
+
+/// Differences of every non-reference series against the reference at a step, missing where
+/// either series has no value.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct LossDiff {
+    pub step: f64,
+    pub abs: Vec<Option<f64>>,
+    pub rel: Vec<Option<f64>>,
+    /// The reference value the diffs are taken against.
+    pub base: Option<f64>,
+}
+
+/// Percentiles of a distribution, interpolated linearly between the closest ranks.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub struct Percentiles {
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+}
+
+impl Percentiles {
+    pub fn of(mut values: Vec<f64>) -> Self {
+        values.sort_by(f64::total_cmp);
+        let percentile = |p: f64| {
+            let rank = p / 100.0 * values.len().saturating_sub(1) as f64;
+            let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+            match (values.get(low), values.get(high)) {
+                (Some(low_value), Some(high_value)) => {
+                    low_value + (high_value - low_value) * (rank - low as f64)
+                }
+                _ => f64::NAN,
+            }
+        };
+        Self {
+            p50: percentile(50.0),
+            p90: percentile(90.0),
+            p99: percentile(99.0),
+        }
+    }
+}
+
+/// A statistic of [`Feature::stats`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stat {
+    pub name: &'static str,
+    /// Missing when no step had a value for it.
+    pub value: Option<f64>,
+    /// Whether the statistic is the value at one step, as a min or max is.
+    pub at_step: bool,
+    pub step: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub struct Feature {
+    /// Diffs with their step, missing when no step was compared.
+    pub max_diff: Option<(f64, usize)>,
+    pub min_diff: Option<(f64, usize)>,
+    pub mean_diff: f64,
+    pub max_abs_diff: Option<(f64, usize)>,
+    pub min_abs_diff: Option<(f64, usize)>,
+    pub mean_abs_diff: f64,
+    pub mean_rel_diff: f64,
+    pub mean_abs_rel_diff: f64,
+    /// The smallest non-negative diff, missing when the series never reached the reference.
+    pub min_p_diff: Option<(f64, usize)>,
+    /// The largest negative diff, missing when the series never went below the reference.
+    pub max_n_diff: Option<(f64, usize)>,
+    /// Standard deviation of the diff.
+    pub std_diff: f64,
+    /// Root mean square of the diff.
+    pub rmse: f64,
+    pub abs_diff_percentiles: Percentiles,
+    pub abs_rel_diff_percentiles: Percentiles,
+    /// Pearson correlation between the compared curve and the reference.
+    pub pearson: f64,
+    /// Steps whose abs or rel diff is NaN, left out of the statistics of that diff.
+    pub nan: Vec<usize>,
+    /// Steps whose abs or rel diff is infinite but not NaN, left out of the statistics of that
+    /// diff.
+    pub inf: Vec<usize>,
+}
+
+impl Feature {
+    /// Every statistic, in report order.
+    pub fn stats(&self) -> [Stat; 19] {
+        let at_step = |name, value: Option<(f64, usize)>| Stat {
+            name,
+            value: value.map(|(value, _)| value),
+            at_step: true,
+            step: value.map(|(_, step)| step),
+        };
+        let over_steps = |name, value| Stat {
+            name,
+            value: Some(value),
+            at_step: false,
+            step: None,
+        };
+        [
+            at_step("Max Diff", self.max_diff),
+            at_step("Min Diff", self.min_diff),
+            at_step("Max Abs Diff", self.max_abs_diff),
+            at_step("Min Abs Diff", self.min_abs_diff),
+            at_step("Max N-Diff", self.max_n_diff),
+            at_step("Min P-Diff", self.min_p_diff),
+            over_steps("Mean Diff", self.mean_diff),
+            over_steps("Mean Abs Diff", self.mean_abs_diff),
+            over_steps("Mean Rel Diff", self.mean_rel_diff),
+            over_steps("Mean Abs Rel Diff", self.mean_abs_rel_diff),
+            over_steps("Std Diff", self.std_diff),
+            over_steps("RMSE", self.rmse),
+            over_steps("P50 Abs Diff", self.abs_diff_percentiles.p50),
+            over_steps("P90 Abs Diff", self.abs_diff_percentiles.p90),
+            over_steps("P99 Abs Diff", self.abs_diff_percentiles.p99),
+            over_steps("P50 Abs Rel Diff", self.abs_rel_diff_percentiles.p50),
+            over_steps("P90 Abs Rel Diff", self.abs_rel_diff_percentiles.p90),
+            over_steps("P99 Abs Rel Diff", self.abs_rel_diff_percentiles.p99),
+            over_steps("Pearson", self.pearson),
+        ]
+    }
+
+    /// The first few NaN steps, e.g. `3 (step=10, 20, 30)`.
+    pub fn nan_summary(&self) -> String {
+        steps_summary(&self.nan)
+    }
+
+    /// The first few infinite steps, e.g. `3 (step=10, 20, 30)`.
+    pub fn inf_summary(&self) -> String {
+        steps_summary(&self.inf)
+    }
+}
+
+fn steps_summary(steps: &[usize]) -> String {
+    const SHOWN: usize = 10;
+    if steps.is_empty() {
+        return "0".to_string();
+    }
+    let mut shown = steps
+        .iter()
+        .take(SHOWN)
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    if steps.len() > SHOWN {
+        shown += &format!(" and {} more", steps.len() - SHOWN);
+    }
+    format!("{} (step={shown})", steps.len())
+}
+
+/// Reads the mapped columns of every row, handling bad rows according to `policy`.
+///
+/// Every bad cell is reported, whatever the policy. Flagged rows keep their bad cells as missing
+/// values so that the charts show a gap, a flagged step column drops the row since it has no
+/// position. NaN and infinite losses are not bad cells, they are left to the statistics.
+pub fn table_to_loss(
+    table: &Table,
+    mapping: &ColumnMapping,
+    policy: BadRowPolicy,
+) -> (Vec<Loss>, Vec<RowError>) {
+    let mut loss = Vec::with_capacity(table.rows.len());
+    let mut errors = table.errors.clone();
+    for (line, cells) in &table.rows {
+        let mut report = |message: String| {
+            errors.push(RowError {
+                line: *line,
+                message,
+            })
+        };
+        let step = table.finite_value(cells, mapping.step).map_err(&mut report);
+        let values = mapping
+            .series
+            .iter()
+            .map(|&column| table.value(cells, column).map_err(&mut report))
+            .collect::<Vec<_>>();
+        let Ok(step) = step else {
+            continue;
+        };
+        match (values.iter().all(Result::is_ok), policy) {
+            (true, _) | (false, BadRowPolicy::Flag) => loss.push(Loss {
+                step,
+                values: values.into_iter().map(Result::ok).collect(),
+                smoothed: vec![],
+            }),
+            (false, _) => {}
+        }
+    }
+    if policy == BadRowPolicy::Reject && !errors.is_empty() {
+        loss.clear();
+    }
+    (loss, errors)
+}
+
+/// Toast body listing the first few bad rows.
+pub fn row_errors_summary(errors: &[RowError], policy: BadRowPolicy) -> String {
+    const SHOWN: usize = 5;
+    let action = match policy {
+        BadRowPolicy::Skip => "skipped",
+        BadRowPolicy::Flag => "plotted as gaps",
+        BadRowPolicy::Reject => "rejected the file",
+    };
+    let mut summary = format!("{} bad cell(s), {action}. ", errors.len());
+    summary += &errors
+        .iter()
+        .take(SHOWN)
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ");
+    if errors.len() > SHOWN {
+        summary += &format!("; and {} more", errors.len() - SHOWN);
+    }
+    summary
+}
+
+/// Diffs every series against the series at `reference`, using the smoothed values if
+/// `smoothed` is set.
+pub fn calculate_diff(loss: &[Loss], reference: usize, smoothed: bool) -> Vec<LossDiff> {
+    loss.iter()
+        .map(|record| {
+            let values = match smoothed {
+                true => &record.smoothed,
+                false => &record.values,
+            };
+            let base = values.get(reference).copied().flatten();
+            let (abs, rel) = values
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != reference)
+                .map(|(_, value)| match (*value, base) {
+                    (Some(value), Some(base)) => {
+                        let abs = value - base;
+                        (Some(abs), Some(abs / base))
+                    }
+                    _ => (None, None),
+                })
+                .unzip();
+            LossDiff {
+                step: record.step,
+                abs,
+                rel,
+                base,
+            }
+        })
+        .collect()
+}
+
+/// Statistics of the `series`-th diff series.
+///
+/// Steps missing either series are skipped. A NaN or infinite abs diff leaves the step out of
+/// the abs statistics and a NaN or infinite rel diff out of the rel statistics, so a zero
+/// reference still counts towards the abs diffs. Such steps are listed in [`Feature::nan`] or
+/// [`Feature::inf`].
+pub fn calculate_feature(diffs: &[LossDiff], series: usize) -> Feature {
+    let mut max_diff: Option<(f64, usize)> = None;
+    let mut min_diff: Option<(f64, usize)> = None;
+    let mut sum_diff = 0.0;
+    let mut max_abs_diff: Option<(f64, usize)> = None;
+    let mut min_abs_diff: Option<(f64, usize)> = None;
+    let mut sum_abs_diff = 0.0;
+    let mut min_p_diff: Option<(f64, usize)> = None;
+    let mut max_n_diff: Option<(f64, usize)> = None;
+    let mut nan = vec![];
+    let mut inf = vec![];
+    let diffs = diffs
+        .iter()
+        .filter_map(|diff| {
+            let abs = diff.abs.get(series).copied().flatten()?;
+            let rel = diff.rel.get(series).copied().flatten()?;
+            Some((diff.step, abs, rel, diff.base?))
+        })
+        .collect::<Vec<_>>();
+    for &(step, abs, rel, _) in &diffs {
+        match (
+            abs.is_nan() || rel.is_nan(),
+            abs.is_infinite() || rel.is_infinite(),
+        ) {
+            (true, _) => nan.push(step as usize),
+            (false, true) => inf.push(step as usize),
+            (false, false) => {}
+        }
+    }
+    let abs_diffs = diffs
+        .iter()
+        .filter(|(_, abs, _, _)| abs.is_finite())
+        .collect::<Vec<_>>();
+    let rels = diffs
+        .iter()
+        .map(|(_, _, rel, _)| *rel)
+        .filter(|rel| rel.is_finite())
+        .collect::<Vec<_>>();
+    // the first step keeps a tie
+    let keep_max = |kept: &mut Option<(f64, usize)>, value: f64, step: usize| {
+        if kept.is_none_or(|(max, _)| max < value) {
+            *kept = Some((value, step));
+        }
+    };
+    let keep_min = |kept: &mut Option<(f64, usize)>, value: f64, step: usize| {
+        if kept.is_none_or(|(min, _)| min > value) {
+            *kept = Some((value, step));
+        }
+    };
+    for &&(step, abs, _, _) in &abs_diffs {
+        let step = step as usize;
+        keep_max(&mut max_diff, abs, step);
+        keep_min(&mut min_diff, abs, step);
+        keep_max(&mut max_abs_diff, abs.abs(), step);
+        keep_min(&mut min_abs_diff, abs.abs(), step);
+        sum_diff += abs;
+        sum_abs_diff += abs.abs();
+        match abs >= 0.0 {
+            true => keep_min(&mut min_p_diff, abs, step),
+            false => keep_max(&mut max_n_diff, abs, step),
+        }
+    }
+
+    let mut feature = Feature {
+        nan,
+        inf,
+        ..Feature::default()
+    };
+    if !abs_diffs.is_empty() {
+        let n = abs_diffs.len() as f64;
+        let mean_diff = sum_diff / n;
+        let variance = abs_diffs
+            .iter()
+            .map(|(_, abs, _, _)| (abs - mean_diff).powi(2))
+            .sum::<f64>()
+            / n;
+        let mean_square = abs_diffs
+            .iter()
+            .map(|(_, abs, _, _)| abs * abs)
+            .sum::<f64>()
+            / n;
+        feature = Feature {
+            max_diff,
+            min_diff,
+            mean_diff,
+            max_abs_diff,
+            min_abs_diff,
+            mean_abs_diff: sum_abs_diff / n,
+            min_p_diff,
+            max_n_diff,
+            std_diff: variance.sqrt(),
+            rmse: mean_square.sqrt(),
+            abs_diff_percentiles: Percentiles::of(
+                abs_diffs.iter().map(|(_, abs, _, _)| abs.abs()).collect(),
+            ),
+            pearson: pearson(
+                abs_diffs
+                    .iter()
+                    .map(|&&(_, abs, _, base)| (base, base + abs)),
+            ),
+            ..feature
+        };
+    }
+    if !rels.is_empty() {
+        let n = rels.len() as f64;
+        feature = Feature {
+            mean_rel_diff: rels.iter().sum::<f64>() / n,
+            mean_abs_rel_diff: rels.iter().map(|rel| rel.abs()).sum::<f64>() / n,
+            abs_rel_diff_percentiles: Percentiles::of(rels.iter().map(|rel| rel.abs()).collect()),
+            ..feature
+        };
+    }
+    feature
+}
+
+/// Pearson correlation of the pairs, NaN when either side is constant.
+fn pearson(pairs: impl Iterator<Item = (f64, f64)> + Clone) -> f64 {
+    let n = pairs.clone().count() as f64;
+    let (mean_x, mean_y) = pairs
+        .clone()
+        .fold((0.0, 0.0), |(x, y), (a, b)| (x + a / n, y + b / n));
+    let (mut covariance, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        covariance += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    covariance / (var_x * var_y).sqrt()
+}
+
+/// Bins of a histogram, with a count per diff series.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistogramBin {
+    pub start: f64,
+    pub end: f64,
+    pub counts: Vec<usize>,
+}
+
+/// Histogram of the finite diffs of the given diff series, over `bins` equal bins spanning all
+/// of them.
+pub fn diff_histogram(diffs: &[LossDiff], series: &[usize], bins: usize) -> Vec<HistogramBin> {
+    let values = series
+        .iter()
+        .map(|&series| {
+            diffs
+                .iter()
+                .filter_map(|diff| diff.abs.get(series).copied().flatten())
+                .filter(|abs| abs.is_finite())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let (min, max) = values
+        .iter()
+        .flatten()
+        .fold((f64::MAX, f64::MIN), |(min, max), &value| {
+            (min.min(value), max.max(value))
+        });
+    if bins == 0 || min > max {
+        return vec![];
+    }
+    // a constant diff still gets a bin of some width
+    let width = match max > min {
+        true => (max - min) / bins as f64,
+        false => 1.0,
+    };
+    let mut histogram = (0..bins)
+        .map(|i| HistogramBin {
+            start: min + width * i as f64,
+            end: min + width * (i + 1) as f64,
+            counts: vec![0; series.len()],
+        })
+        .collect::<Vec<_>>();
+    for (i, values) in values.iter().enumerate() {
+        for value in values {
+            let bin = (((value - min) / width) as usize).min(bins - 1);
+            histogram[bin].counts[i] += 1;
+        }
+    }
+    histogram
+}
+
+/// Statistics of the given diff series, labelled with the names of the compared series.
+pub fn calculate_features(
+    diffs: &[LossDiff],
+    compared: &[(usize, SeriesInfo)],
+) -> Vec<(String, Feature)> {
+    compared
+        .iter()
+        .map(|(series, info)| (info.name.clone(), calculate_feature(diffs, *series)))
+        .collect()
+}
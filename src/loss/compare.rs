@@ -0,0 +1,377 @@
+use super::format::{Format, ParseOptions};
+use super::run::{runs_to_loss, Join, Run};
+use super::table::BadRowPolicy;
+use super::tolerance::{verdict, Check, Metric, Rule};
+use super::{calculate_diff, calculate_feature, row_errors_summary, Feature};
+use serde_json::json;
+
+const USAGE: &str = "\
+Usage: llm-tools compare <REFERENCE> <CANDIDATE> [OPTIONS]
+
+Compares the loss curves of two files and exits with 1 when a tolerance is exceeded or the
+candidate loss is NaN or infinite after the warmup.
+
+Options:
+  --format <FORMAT>         auto, csv, json-jsonl, tensorboard, megatron-log, deepspeed-log,
+                            hf-trainer-log, paddlenlp-log or custom-regex [default: auto]
+  --pattern <REGEX>         named captures for custom-regex
+  --step-key <PATH>         dotted step path of JSON records
+  --metric-keys <PATHS>     comma separated dotted metric paths of JSON records
+  --join <JOIN>             inner, outer or interpolated [default: outer]
+  --bad-rows <POLICY>       skip, flag or reject [default: skip]
+  --start <STEP>            first compared step
+  --end <STEP>              last compared step
+  --rule <METRIC=MAX[@STEP]>
+                            tolerance, e.g. mean-abs-rel-diff=0.01 or max-abs-diff=0.1@100,
+                            may be repeated [default: the draw page rules]
+  --json                    print JSON instead of a table";
+
+/// Everything `compare` is told on the command line.
+#[derive(Clone, Debug)]
+struct Args {
+    files: Vec<String>,
+    format: Format,
+    options: ParseOptions,
+    join: Join,
+    policy: BadRowPolicy,
+    start: f64,
+    end: f64,
+    rules: Vec<Rule>,
+    json: bool,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut parsed = Args {
+            files: vec![],
+            format: Format::default(),
+            options: ParseOptions::default(),
+            join: Join::default(),
+            policy: BadRowPolicy::default(),
+            start: f64::MIN,
+            end: f64::MAX,
+            rules: vec![],
+            json: false,
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| format!("`{arg}` needs a value"))
+            };
+            let number = |value: String| {
+                value
+                    .parse::<f64>()
+                    .map_err(|_| format!("`{arg}` needs a number, got `{value}`"))
+            };
+            match arg.as_str() {
+                "--format" => parsed.format = choice(&Format::ALL, &value()?, "format")?,
+                "--pattern" => parsed.options.pattern = value()?,
+                "--step-key" => parsed.options.step_key = value()?,
+                "--metric-keys" => parsed.options.metric_keys = value()?,
+                "--join" => parsed.join = choice(&Join::ALL, &value()?, "join")?,
+                "--bad-rows" => parsed.policy = choice(&BadRowPolicy::ALL, &value()?, "policy")?,
+                "--start" => parsed.start = number(value()?)?,
+                "--end" => parsed.end = number(value()?)?,
+                "--rule" => parsed.rules.push(parse_rule(&value()?)?),
+                "--json" => parsed.json = true,
+                "-h" | "--help" => return Err(String::new()),
+                flag if flag.starts_with("--") => return Err(format!("unknown option `{flag}`")),
+                file => parsed.files.push(file.to_string()),
+            }
+        }
+        if parsed.files.len() != 2 {
+            return Err("expected a reference and a candidate file".to_string());
+        }
+        if parsed.rules.is_empty() {
+            parsed.rules = Rule::defaults();
+        }
+        Ok(parsed)
+    }
+}
+
+/// The statistics and tolerance checks of one candidate series.
+#[derive(Clone, Debug)]
+struct Report {
+    name: String,
+    feature: Feature,
+    checks: Vec<(Rule, Check)>,
+}
+
+impl Report {
+    /// Whether every rule holds and no diff is NaN or infinite from the earliest warmup on.
+    fn passed(&self) -> bool {
+        let warmup = self
+            .checks
+            .iter()
+            .map(|(rule, _)| rule.warmup)
+            .fold(f64::INFINITY, f64::min);
+        let mut non_finite = self.feature.nan.iter().chain(&self.feature.inf);
+        self.checks.iter().all(|(_, check)| check.passed)
+            && non_finite.all(|step| (*step as f64) < warmup)
+    }
+}
+
+/// Runs `compare` with the arguments following the subcommand, returning the exit code.
+///
+/// 0 when every tolerance holds, 1 when one is exceeded or a diff is NaN or infinite after the
+/// warmup and 2 when the files cannot be compared.
+pub fn main(args: &[String]) -> i32 {
+    let args = match Args::parse(args) {
+        Ok(args) => args,
+        Err(err) => {
+            if !err.is_empty() {
+                eprintln!("error: {err}\n");
+            }
+            eprintln!("{USAGE}");
+            return 2;
+        }
+    };
+    match compare(&args) {
+        Ok((reference, reports)) => {
+            let passed = reports.iter().all(Report::passed);
+            match args.json {
+                true => println!("{}", to_json(&reference, &reports, passed)),
+                false => print!("{}", to_table(&reference, &reports, passed)),
+            }
+            match passed {
+                true => 0,
+                false => 1,
+            }
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            2
+        }
+    }
+}
+
+/// Reads both files and checks every candidate series against the reference series.
+fn compare(args: &Args) -> Result<(String, Vec<Report>), String> {
+    let mut runs = vec![];
+    for (index, path) in args.files.iter().enumerate() {
+        let bytes = std::fs::read(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+        let file_name = std::path::Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.clone());
+        let (table, _) = args
+            .format
+            .parse(&file_name, &bytes, &args.options)
+            .map_err(|e| format!("cannot parse {path}: {e}"))?;
+        runs.push(Run::new(&file_name, table, index));
+    }
+
+    let (infos, loss, errors) = runs_to_loss(&runs, args.policy, args.join);
+    for (id, errors) in errors.iter().filter(|(_, errors)| !errors.is_empty()) {
+        let name = runs.iter().find(|run| run.id == *id).map(|run| &run.name);
+        eprintln!(
+            "warning: bad rows in {}: {}",
+            name.cloned().unwrap_or_default(),
+            row_errors_summary(errors, args.policy)
+        );
+    }
+    let reference = runs[0]
+        .reference()
+        .and_then(|key| infos.iter().position(|info| info.key == key))
+        .ok_or_else(|| format!("{} has no loss column", args.files[0]))?;
+    let loss = loss
+        .into_iter()
+        .filter(|row| args.start <= row.step && row.step <= args.end)
+        .collect::<Vec<_>>();
+    let diffs = calculate_diff(&loss, reference, false);
+
+    let reports = infos
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != reference)
+        .enumerate()
+        .map(|(series, (_, info))| Report {
+            name: info.name.clone(),
+            feature: calculate_feature(&diffs, series),
+            checks: verdict(&args.rules, &diffs, series),
+        })
+        .collect::<Vec<_>>();
+    if reports.is_empty() {
+        return Err(format!("{} has no loss column", args.files[1]));
+    }
+    Ok((infos[reference].name.clone(), reports))
+}
+
+fn to_table(reference: &str, reports: &[Report], passed: bool) -> String {
+    let mut table = String::new();
+    for report in reports {
+        let f = &report.feature;
+        table += &format!("{} vs {reference}\n", report.name);
+        for stat in f.stats() {
+            let name = stat.name;
+            let value = match stat.value {
+                Some(value) => format!("{value:.6}"),
+                None => "null".to_string(),
+            };
+            match (stat.at_step, stat.step) {
+                (true, Some(step)) => table += &format!("  {name:<20}{value:>14}  step={step}\n"),
+                (true, None) => table += &format!("  {name:<20}{value:>14}  step=null\n"),
+                (false, _) => table += &format!("  {name:<20}{value:>14}\n"),
+            }
+        }
+        table += &format!("  {:<20}{:>14}\n", "NaN", f.nan_summary());
+        table += &format!("  {:<20}{:>14}\n", "Inf", f.inf_summary());
+        for (rule, check) in &report.checks {
+            let outcome = match (check.passed, check.first_violation) {
+                (true, _) => "PASS".to_string(),
+                (false, Some(step)) => format!("FAIL, first violated at step {step}"),
+                (false, None) => "FAIL, no steps checked".to_string(),
+            };
+            table += &format!(
+                "  {:<40}{:>14.6}  {outcome}\n",
+                rule.to_string(),
+                check.value
+            );
+        }
+        table += "\n";
+    }
+    table += match passed {
+        true => "PASS\n",
+        false => "FAIL\n",
+    };
+    table
+}
+
+fn to_json(reference: &str, reports: &[Report], passed: bool) -> String {
+    let series = reports
+        .iter()
+        .map(|report| {
+            let checks = report
+                .checks
+                .iter()
+                .map(|(rule, check)| {
+                    json!({
+                        "rule": rule.to_string(),
+                        "value": check.value,
+                        "passed": check.passed,
+                        "first_violation": check.first_violation,
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "name": report.name,
+                "feature": report.feature,
+                "checks": checks,
+            })
+        })
+        .collect::<Vec<_>>();
+    let report = json!({
+        "reference": reference,
+        "passed": passed,
+        "series": series,
+    });
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
+
+/// `mean-abs-rel-diff=0.01` or `max-abs-diff=0.1@100`.
+fn parse_rule(rule: &str) -> Result<Rule, String> {
+    let invalid = || format!("invalid rule `{rule}`, expected METRIC=MAX[@STEP]");
+    let (metric, bound) = rule.split_once('=').ok_or_else(invalid)?;
+    let (threshold, warmup) = bound.split_once('@').unwrap_or((bound, "0"));
+    Ok(Rule::new(
+        choice(&Metric::ALL, metric, "metric")?,
+        threshold.trim().parse().map_err(|_| invalid())?,
+        warmup.trim().parse().map_err(|_| invalid())?,
+    ))
+}
+
+/// The option whose display name, in kebab case, is `value`.
+fn choice<T: Copy + std::fmt::Display>(all: &[T], value: &str, what: &str) -> Result<T, String> {
+    let kebab = |name: String| {
+        name.to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join("-")
+    };
+    all.iter()
+        .copied()
+        .find(|option| kebab(option.to_string()) == value.trim().to_lowercase())
+        .ok_or_else(|| {
+            let options = all
+                .iter()
+                .map(|option| kebab(option.to_string()))
+                .collect::<Vec<_>>();
+            format!(
+                "unknown {what} `{value}`, expected one of {}",
+                options.join(", ")
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Compares two `step,loss` files with the given loss values, returning the exit code.
+    fn exit_code(name: &str, reference: &[&str], candidate: &[&str], args: &[&str]) -> i32 {
+        let dir = std::env::temp_dir();
+        let mut files = vec![];
+        for (run, losses) in [("reference", reference), ("candidate", candidate)] {
+            let path = dir.join(format!("compare-{}-{name}-{run}.csv", std::process::id()));
+            let rows = losses
+                .iter()
+                .enumerate()
+                .map(|(i, loss)| format!("{},{loss}\n", i + 1))
+                .collect::<String>();
+            std::fs::write(&path, format!("step,loss\n{rows}")).unwrap();
+            files.push(path.to_string_lossy().into_owned());
+        }
+        let args = files
+            .iter()
+            .cloned()
+            .chain(args.iter().map(|arg| arg.to_string()))
+            .collect::<Vec<_>>();
+        let code = main(&args);
+        for file in files {
+            let _ = std::fs::remove_file(file);
+        }
+        code
+    }
+
+    #[test]
+    fn aligned_runs_pass() {
+        let losses = ["2.0", "1.9", "1.8", "1.7"];
+        let args = ["--rule", "max-abs-diff=0.1"];
+        assert_eq!(exit_code("aligned", &losses, &losses, &args), 0);
+    }
+
+    #[test]
+    fn nan_candidate_after_warmup_fails() {
+        let reference = ["2.0", "1.9", "1.8", "1.7"];
+        let candidate = ["2.0", "1.9", "NaN", "1.7"];
+        assert_eq!(exit_code("nan", &reference, &candidate, &[]), 1);
+    }
+
+    #[test]
+    fn inf_candidate_after_warmup_fails() {
+        let reference = ["2.0", "1.9", "1.8", "1.7"];
+        let candidate = ["2.0", "1.9", "1.8", "inf"];
+        let args = ["--rule", "max-abs-diff=0.1@2"];
+        assert_eq!(exit_code("inf", &reference, &candidate, &args), 1);
+    }
+
+    #[test]
+    fn nan_candidate_during_warmup_passes() {
+        let reference = ["2.0", "1.9", "1.8", "1.7"];
+        let candidate = ["NaN", "1.9", "1.8", "1.7"];
+        let args = ["--rule", "max-abs-diff=0.1@2"];
+        assert_eq!(exit_code("warmup", &reference, &candidate, &args), 0);
+    }
+
+    #[test]
+    fn non_finite_rel_diff_fails_an_abs_rule() {
+        // a zero reference makes the rel diff infinite, which no abs rule looks at
+        let reference = ["2.0", "1.9", "0.0", "1.7"];
+        let candidate = ["2.0", "1.9", "0.05", "1.7"];
+        let args = ["--rule", "max-abs-diff=0.1"];
+        assert_eq!(exit_code("zero", &reference, &candidate, &args), 1);
+    }
+}
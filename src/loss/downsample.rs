@@ -0,0 +1,113 @@
+use super::{Loss, LossDiff};
+
+/// A row of values drawn against its step.
+pub trait Plotted: Clone {
+    fn step(&self) -> f64;
+    /// Every value drawn for the row, one per line.
+    fn values(&self) -> Vec<Option<f64>>;
+}
+
+impl Plotted for Loss {
+    fn step(&self) -> f64 {
+        self.step
+    }
+
+    fn values(&self) -> Vec<Option<f64>> {
+        self.values.iter().chain(&self.smoothed).copied().collect()
+    }
+}
+
+impl Plotted for LossDiff {
+    fn step(&self) -> f64 {
+        self.step
+    }
+
+    fn values(&self) -> Vec<Option<f64>> {
+        self.abs.iter().chain(&self.rel).copied().collect()
+    }
+}
+
+/// The rows that keep the shape of every line with about `threshold` points each.
+///
+/// The first and last row of every run of NaN or infinite values of a line, and the rows `keep`
+/// asks for, are always kept, so no gap or event drops out of the chart.
+pub fn downsample<T: Plotted>(rows: &[T], threshold: usize, keep: impl Fn(&T) -> bool) -> Vec<T> {
+    if rows.len() <= threshold {
+        return rows.to_vec();
+    }
+    let values = rows.iter().map(Plotted::values).collect::<Vec<_>>();
+    let mut kept = rows.iter().map(keep).collect::<Vec<_>>();
+    let lines = values.iter().map(Vec::len).max().unwrap_or(0);
+    for line in 0..lines {
+        let non_finite = |i: usize| {
+            values[i]
+                .get(line)
+                .copied()
+                .flatten()
+                .is_some_and(|value| !value.is_finite())
+        };
+        for (i, kept) in kept.iter_mut().enumerate() {
+            let bounds = i == 0 || !non_finite(i - 1) || i + 1 == rows.len() || !non_finite(i + 1);
+            *kept |= bounds && non_finite(i);
+        }
+        let (rows_of_points, points): (Vec<_>, Vec<_>) = values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, values)| {
+                let value = values.get(line).copied().flatten()?;
+                value.is_finite().then(|| (i, (rows[i].step(), value)))
+            })
+            .unzip();
+        for point in lttb(&points, threshold) {
+            kept[rows_of_points[point]] = true;
+        }
+    }
+    rows.iter()
+        .zip(kept)
+        .filter(|(_, kept)| *kept)
+        .map(|(row, _)| row.clone())
+        .collect()
+}
+
+/// Indices of the `threshold` points picked by largest-triangle-three-buckets, always with the
+/// first and the last point.
+///
+/// The points in between are split into buckets, and every bucket keeps the point spanning the
+/// largest triangle with the point kept before it and the mean of the next bucket.
+pub fn lttb(points: &[(f64, f64)], threshold: usize) -> Vec<usize> {
+    let len = points.len();
+    if threshold >= len || threshold < 3 {
+        return (0..len).collect();
+    }
+    let every = (len - 2) as f64 / (threshold - 2) as f64;
+    let bucket = |i: usize| {
+        let start = (i as f64 * every) as usize + 1;
+        let end = (((i + 1) as f64 * every) as usize + 1).min(len);
+        start..end
+    };
+
+    let mut kept = Vec::with_capacity(threshold);
+    kept.push(0);
+    let mut last = 0;
+    for i in 0..threshold - 2 {
+        // the bucket after the last one is just the last point
+        let next = &points[bucket(i + 1)];
+        let n = next.len() as f64;
+        let mean_x = next.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = next.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+        let (last_x, last_y) = points[last];
+        let area = |j: usize| {
+            let (x, y) = points[j];
+            ((last_x - mean_x) * (y - last_y) - (last_x - x) * (mean_y - last_y)).abs()
+        };
+        let range = bucket(i);
+        let start = range.start;
+        last = range
+            .max_by(|&a, &b| area(a).total_cmp(&area(b)))
+            .unwrap_or(start);
+        kept.push(last);
+    }
+    kept.push(len - 1);
+    kept
+}
@@ -0,0 +1,232 @@
+use super::run::SeriesInfo;
+use super::{Loss, LossDiff};
+use std::collections::VecDeque;
+
+/// What happened at a step.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    /// The loss jumped above its recent values.
+    Spike,
+    /// The loss was logged as NaN or infinite, from this step on.
+    NonFinite,
+    /// The first step a compared series drifted away from the reference.
+    Divergence,
+}
+
+impl std::fmt::Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Spike => "Spike",
+            Self::NonFinite => "NaN/Inf",
+            Self::Divergence => "Divergence",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LossEvent {
+    pub kind: EventKind,
+    pub step: f64,
+    /// The series the event happened in, the compared series for a divergence.
+    pub series: String,
+    /// Where the marker is drawn: the loss of a spike, the last finite loss before NaN/Inf
+    /// steps and the relative diff of a divergence.
+    pub value: f64,
+    pub detail: String,
+}
+
+/// Thresholds of the event detection.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EventOptions {
+    /// Number of preceding steps a spike is measured against.
+    pub window: usize,
+    /// Z-score above which a step is a spike.
+    pub z_score: f64,
+    /// Absolute relative diff above which a compared series has diverged.
+    pub divergence: f64,
+    /// Number of consecutive steps the relative diff has to stay beyond `divergence`, so that
+    /// a single noisy step is no divergence.
+    pub divergence_steps: usize,
+}
+
+impl Default for EventOptions {
+    fn default() -> Self {
+        Self {
+            window: 50,
+            z_score: 4.0,
+            divergence: 0.05,
+            divergence_steps: 10,
+        }
+    }
+}
+
+/// Spikes, NaN/Inf steps and divergences of the plotted series, ordered by step.
+///
+/// `infos` are the series of `loss`, `compared` the diff series of `diffs` with their
+/// position.
+pub fn detect_events(
+    infos: &[SeriesInfo],
+    loss: &[Loss],
+    diffs: &[LossDiff],
+    compared: &[(usize, SeriesInfo)],
+    options: &EventOptions,
+) -> Vec<LossEvent> {
+    let mut events = vec![];
+    for (i, info) in infos.iter().enumerate() {
+        for (step, value, z_score) in spikes(loss, i, options.window, options.z_score) {
+            events.push(LossEvent {
+                kind: EventKind::Spike,
+                step,
+                series: info.name.clone(),
+                value,
+                detail: format!("loss {value:.6}, z-score {z_score:.1}"),
+            });
+        }
+        for run in non_finite_runs(loss, i) {
+            events.push(LossEvent {
+                kind: EventKind::NonFinite,
+                step: run.step,
+                series: info.name.clone(),
+                value: run.last_finite,
+                detail: match run.steps {
+                    1 => format!("loss {}", run.value),
+                    steps => format!("loss {} for {steps} steps", run.value),
+                },
+            });
+        }
+    }
+    for (series, info) in compared {
+        let divergence =
+            first_divergence(diffs, *series, options.divergence, options.divergence_steps);
+        if let Some((step, rel)) = divergence {
+            events.push(LossEvent {
+                kind: EventKind::Divergence,
+                step,
+                series: info.name.clone(),
+                value: rel,
+                detail: format!(
+                    "rel diff {rel:.6} beyond {} for {} steps",
+                    options.divergence,
+                    options.divergence_steps.max(1)
+                ),
+            });
+        }
+    }
+    events.sort_by(|a, b| a.step.total_cmp(&b.step));
+    events
+}
+
+/// Steps of the `series`-th series whose z-score against the `window` preceding finite
+/// values reaches `threshold`, as `(step, value, z-score)`.
+pub fn spikes(loss: &[Loss], series: usize, window: usize, threshold: f64) -> Vec<(f64, f64, f64)> {
+    let window = window.max(2);
+    let mut recent = VecDeque::with_capacity(window);
+    let (mut sum, mut sum_sq) = (0.0, 0.0);
+    let mut spikes = vec![];
+    for row in loss {
+        let Some(value) = row.values.get(series).copied().flatten() else {
+            continue;
+        };
+        if !value.is_finite() {
+            continue;
+        }
+        if recent.len() == window {
+            let n = window as f64;
+            let mean = sum / n;
+            let std = (sum_sq / n - mean * mean).max(0.0).sqrt();
+            let z_score = (value - mean) / std;
+            if std > 0.0 && z_score >= threshold {
+                spikes.push((row.step, value, z_score));
+            }
+            let oldest: f64 = recent.pop_front().unwrap_or_default();
+            sum -= oldest;
+            sum_sq -= oldest * oldest;
+        }
+        recent.push_back(value);
+        sum += value;
+        sum_sq += value * value;
+    }
+    spikes
+}
+
+/// Consecutive steps a series logged a NaN or infinite loss at.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NonFiniteRun {
+    /// The first step of the run.
+    pub step: f64,
+    /// The loss logged at the first step.
+    pub value: f64,
+    pub steps: usize,
+    /// The last finite loss before the run, NaN when there is none.
+    pub last_finite: f64,
+}
+
+/// The runs of NaN or infinite losses of the `series`-th series.
+///
+/// Steps the series did not log neither end nor extend a run.
+pub fn non_finite_runs(loss: &[Loss], series: usize) -> Vec<NonFiniteRun> {
+    let mut runs: Vec<NonFiniteRun> = vec![];
+    let mut last_finite = f64::NAN;
+    let mut in_run = false;
+    for row in loss {
+        let Some(value) = row.values.get(series).copied().flatten() else {
+            continue;
+        };
+        match (value.is_finite(), in_run) {
+            (true, _) => {
+                last_finite = value;
+                in_run = false;
+            }
+            (false, true) => {
+                if let Some(run) = runs.last_mut() {
+                    run.steps += 1;
+                }
+            }
+            (false, false) => {
+                runs.push(NonFiniteRun {
+                    step: row.step,
+                    value,
+                    steps: 1,
+                    last_finite,
+                });
+                in_run = true;
+            }
+        }
+    }
+    runs
+}
+
+/// The first step from which the `series`-th diff series keeps an absolute relative diff beyond
+/// `threshold` for `steps` consecutive steps, with the diff at that step.
+///
+/// Non-finite relative diffs, such as against a zero reference, are skipped.
+pub fn first_divergence(
+    diffs: &[LossDiff],
+    series: usize,
+    threshold: f64,
+    steps: usize,
+) -> Option<(f64, f64)> {
+    let mut start = None;
+    let mut count = 0;
+    for (step, rel) in diffs
+        .iter()
+        .filter_map(|diff| Some((diff.step, diff.rel.get(series).copied().flatten()?)))
+        .filter(|(_, rel)| rel.is_finite())
+    {
+        match rel.abs() > threshold {
+            true => {
+                let start = *start.get_or_insert((step, rel));
+                count += 1;
+                if count >= steps.max(1) {
+                    return Some(start);
+                }
+            }
+            false => {
+                start = None;
+                count = 0;
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,130 @@
+use super::run::SeriesInfo;
+use super::{Feature, LossDiff};
+use serde_json::json;
+
+/// The statistics of every compared series as CSV, one row per series.
+///
+/// Statistics without a value, such as the max of no diffs, are left empty.
+pub fn features_to_csv(reference: &str, features: &[(String, Feature)]) -> String {
+    let mut header = vec!["Series".to_string(), "Reference".to_string()];
+    for stat in Feature::default().stats() {
+        header.push(stat.name.to_string());
+        if stat.at_step {
+            header.push(format!("{} Step", stat.name));
+        }
+    }
+    for name in ["NaN", "Inf"] {
+        header.push(name.to_string());
+        header.push(format!("{name} Steps"));
+    }
+    let mut csv = csv_row(&header);
+    for (name, feature) in features {
+        let mut row = vec![name.clone(), reference.to_string()];
+        for stat in feature.stats() {
+            row.push(
+                stat.value
+                    .map(|value| value.to_string())
+                    .unwrap_or_default(),
+            );
+            if stat.at_step {
+                row.push(stat.step.map(|step| step.to_string()).unwrap_or_default());
+            }
+        }
+        for steps in [&feature.nan, &feature.inf] {
+            row.push(steps.len().to_string());
+            row.push(
+                steps
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            );
+        }
+        csv += &csv_row(&row);
+    }
+    csv
+}
+
+/// The statistics of every compared series as JSON, shaped like the report of `compare`.
+///
+/// Statistics without a value are null.
+pub fn features_to_json(reference: &str, features: &[(String, Feature)]) -> String {
+    let series = features
+        .iter()
+        .map(|(name, feature)| json!({ "name": name, "feature": feature }))
+        .collect::<Vec<_>>();
+    let report = json!({
+        "reference": reference,
+        "series": series,
+    });
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
+
+/// The reference loss and the abs and rel diff of every compared series per step, missing
+/// values left empty.
+pub fn diffs_to_csv(
+    reference: &str,
+    diffs: &[LossDiff],
+    compared: &[(usize, SeriesInfo)],
+) -> String {
+    let mut header = vec!["Step".to_string(), reference.to_string()];
+    for (_, info) in compared {
+        header.push(format!("{} Abs Diff", info.name));
+        header.push(format!("{} Rel Diff", info.name));
+    }
+    let mut csv = csv_row(&header);
+    let cell = |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_default();
+    for diff in diffs {
+        let mut row = vec![diff.step.to_string(), cell(diff.base)];
+        for (i, _) in compared {
+            row.push(cell(diff.abs.get(*i).copied().flatten()));
+            row.push(cell(diff.rel.get(*i).copied().flatten()));
+        }
+        csv += &csv_row(&row);
+    }
+    csv
+}
+
+/// The reference loss and the abs and rel diff of every compared series per step, as JSON.
+///
+/// Missing and non-finite values are null, as JSON has no NaN.
+pub fn diffs_to_json(
+    reference: &str,
+    diffs: &[LossDiff],
+    compared: &[(usize, SeriesInfo)],
+) -> String {
+    let steps = diffs
+        .iter()
+        .map(|diff| {
+            // in series order, names of renamed runs need not be unique
+            let series = compared
+                .iter()
+                .map(|(i, info)| {
+                    json!({
+                        "name": info.name,
+                        "abs": diff.abs.get(*i).copied().flatten(),
+                        "rel": diff.rel.get(*i).copied().flatten(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({ "step": diff.step, "reference": diff.base, "series": series })
+        })
+        .collect::<Vec<_>>();
+    let export = json!({
+        "reference": reference,
+        "steps": steps,
+    });
+    serde_json::to_string_pretty(&export).unwrap_or_default()
+}
+
+/// One line of CSV, quoting the fields that need it.
+fn csv_row(fields: &[String]) -> String {
+    let fields = fields
+        .iter()
+        .map(|field| match field.contains([',', '"', '\n']) {
+            true => format!("\"{}\"", field.replace('"', "\"\"")),
+            false => field.clone(),
+        })
+        .collect::<Vec<_>>();
+    fields.join(",") + "\n"
+}
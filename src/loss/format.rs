@@ -0,0 +1,174 @@
+use super::json::{metric_keys, JsonReader};
+use super::log::{custom_regex, LogExtractor, LogReader};
+use super::table::{Table, TableReader};
+use super::tfevents::EventReader;
+
+/// Lines of a log tried by every extractor to detect its format.
+const DETECT_LINES: usize = 10_000;
+
+/// How dropped files are read into a table.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// TensorBoard for `events.out.tfevents.*` files, CSV and JSON by extension, otherwise
+    /// the log extractor matching most of the first lines, or CSV.
+    #[default]
+    Auto,
+    Csv,
+    Json,
+    TensorBoard,
+    Log(LogExtractor),
+    CustomRegex,
+}
+
+impl Format {
+    pub const ALL: [Format; 9] = [
+        Self::Auto,
+        Self::Csv,
+        Self::Json,
+        Self::TensorBoard,
+        Self::Log(LogExtractor::Megatron),
+        Self::Log(LogExtractor::DeepSpeed),
+        Self::Log(LogExtractor::HfTrainer),
+        Self::Log(LogExtractor::PaddleNLP),
+        Self::CustomRegex,
+    ];
+
+    /// Reads a file, returning the table and the format it was read as.
+    pub fn parse(
+        &self,
+        file_name: &str,
+        bytes: &[u8],
+        options: &ParseOptions,
+    ) -> Result<(Table, Format), String> {
+        let text = String::from_utf8_lossy(bytes);
+        let (mut reader, format) = self.reader(file_name, bytes, &text, options)?;
+        reader.read(usize::MAX)?;
+        Ok((reader.finish()?, format))
+    }
+
+    /// A reader of a file that reads it a slice at a time, with the format it reads.
+    ///
+    /// `text` is `bytes` decoded as UTF-8, which binary formats ignore. [`Format::Auto`] picks
+    /// TensorBoard for `events.out.tfevents.*` files, CSV and JSON by extension, otherwise the
+    /// log extractor matching most of the first lines, or CSV.
+    pub fn reader<'a>(
+        &self,
+        file_name: &str,
+        bytes: &'a [u8],
+        text: &'a str,
+        options: &ParseOptions,
+    ) -> Result<(FormatReader<'a>, Format), String> {
+        let format = match self {
+            Self::Auto if file_name.contains("tfevents") => Self::TensorBoard,
+            Self::Auto => {
+                let extension = file_name
+                    .rsplit_once('.')
+                    .map(|(_, extension)| extension.to_ascii_lowercase());
+                match extension.as_deref() {
+                    Some("csv" | "tsv") => Self::Csv,
+                    Some("json" | "jsonl" | "ndjson") => Self::Json,
+                    _ => {
+                        let head = match text.match_indices('\n').nth(DETECT_LINES) {
+                            Some((end, _)) => &text[..end],
+                            None => text,
+                        };
+                        match LogExtractor::detect(head) {
+                            Some((extractor, _)) => Self::Log(extractor),
+                            None => Self::Csv,
+                        }
+                    }
+                }
+            }
+            format => *format,
+        };
+        let reader = match format {
+            Self::Auto | Self::Csv => FormatReader::Table(TableReader::new(text)),
+            Self::TensorBoard => FormatReader::Events(EventReader::new(bytes)),
+            Self::Log(extractor) => FormatReader::Log(LogReader::new(text, extractor.regex())),
+            Self::CustomRegex => {
+                FormatReader::Log(LogReader::new(text, custom_regex(&options.pattern)?))
+            }
+            Self::Json => FormatReader::Json {
+                reader: JsonReader::new(text),
+                step_key: options.step_key.clone(),
+                metric_keys: metric_keys(&options.metric_keys),
+            },
+        };
+        Ok((reader, format))
+    }
+}
+
+/// A file being read into a table, see [`Format::reader`].
+pub enum FormatReader<'a> {
+    Table(TableReader<'a>),
+    Events(EventReader<'a>),
+    Log(LogReader<'a>),
+    Json {
+        reader: JsonReader<'a>,
+        step_key: String,
+        metric_keys: Vec<String>,
+    },
+}
+
+impl FormatReader<'_> {
+    /// Records or lines read per slice, about a few milliseconds of work.
+    pub const SLICE: usize = 20_000;
+
+    /// Reads up to `records` more records or lines, returning whether the file is read to its
+    /// end.
+    pub fn read(&mut self, records: usize) -> Result<bool, String> {
+        match self {
+            Self::Table(reader) => Ok(reader.read(records)),
+            Self::Events(reader) => reader.read(records),
+            Self::Log(reader) => Ok(reader.read(records)),
+            Self::Json { reader, .. } => Ok(reader.read(records)),
+        }
+    }
+
+    /// The fraction of the file read so far.
+    pub fn progress(&self) -> f64 {
+        match self {
+            Self::Table(reader) => reader.progress(),
+            Self::Events(reader) => reader.progress(),
+            Self::Log(reader) => reader.progress(),
+            Self::Json { reader, .. } => reader.progress(),
+        }
+    }
+
+    pub fn finish(self) -> Result<Table, String> {
+        match self {
+            Self::Table(reader) => Ok(reader.finish()),
+            Self::Events(reader) => reader.finish(),
+            Self::Log(reader) => reader.finish(),
+            Self::Json {
+                reader,
+                step_key,
+                metric_keys,
+            } => reader.finish(&step_key, &metric_keys),
+        }
+    }
+}
+
+/// Settings of the formats that need more than the file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParseOptions {
+    /// Regex with named captures for [`Format::CustomRegex`].
+    pub pattern: String,
+    /// Dotted path of the step for [`Format::Json`], guessed when empty.
+    pub step_key: String,
+    /// Comma separated dotted paths of the metrics for [`Format::Json`], all when empty.
+    pub metric_keys: String,
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Auto => f.write_str("Auto"),
+            Self::Csv => f.write_str("CSV"),
+            Self::Json => f.write_str("JSON / JSONL"),
+            Self::TensorBoard => f.write_str("TensorBoard"),
+            Self::Log(extractor) => write!(f, "{extractor} Log"),
+            Self::CustomRegex => f.write_str("Custom Regex"),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use super::table::{RowError, Table};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Step keys tried when none is given, W&B logs `_step`.
+const STEP_KEYS: [&str; 4] = ["_step", "step", "global_step", "train/global_step"];
+
+/// Reads JSON Lines, or a JSON array of records such as a W&B history export, into a table.
+///
+/// Nested keys are flattened into dotted paths, e.g. `{"train": {"loss": 2.3}}` becomes the
+/// column `train.loss`. `step_key` defaults to the first of [`STEP_KEYS`] that is present, or
+/// else the record count. Without `metric_keys` every numeric path becomes a column. Bare
+/// `NaN` and `Infinity` tokens, as written by Python, are read as non-finite values.
+pub fn parse_json(text: &str, step_key: &str, metric_keys: &[String]) -> Result<Table, String> {
+    let mut reader = JsonReader::new(text);
+    reader.read(usize::MAX);
+    reader.finish(step_key, metric_keys)
+}
+
+/// Splits `a.b, c.d` into dotted paths.
+pub fn metric_keys(keys: &str) -> Vec<String> {
+    keys.split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Where the records come from: the lines of JSON Lines, or a whole document parsed at once.
+enum Records<'a> {
+    Lines(std::iter::Enumerate<std::str::Lines<'a>>),
+    Document(std::iter::Enumerate<std::vec::IntoIter<Value>>),
+}
+
+/// Reads JSON a few records at a time, see [`parse_json`].
+///
+/// A JSON array has to be parsed as a whole before its records are read.
+pub struct JsonReader<'a> {
+    records: Records<'a>,
+    /// Bytes of JSON Lines or records of a document.
+    total: usize,
+    read: usize,
+    fields: Vec<(usize, Vec<(String, String)>)>,
+    errors: Vec<RowError>,
+}
+
+impl<'a> JsonReader<'a> {
+    pub fn new(text: &'a str) -> Self {
+        // a first line that is a record on its own makes JSON Lines, no need to parse it all
+        let first_line = text.lines().find(|line| !line.trim().is_empty());
+        let record =
+            first_line.and_then(|line| serde_json::from_str::<Value>(&quote_non_finite(line)).ok());
+        let is_record = record
+            .as_ref()
+            .and_then(Value::as_object)
+            .is_some_and(|object| records_key(object).is_none());
+        let document = match is_record {
+            true => None,
+            false => serde_json::from_str::<Value>(&quote_non_finite(text))
+                .ok()
+                .and_then(|document| match document {
+                    Value::Array(array) => Some(array),
+                    Value::Object(mut object) => match object.remove(&records_key(&object)?)? {
+                        Value::Array(array) => Some(array),
+                        _ => None,
+                    },
+                    _ => None,
+                }),
+        };
+        let (records, total) = match document {
+            Some(array) => {
+                let total = array.len();
+                (Records::Document(array.into_iter().enumerate()), total)
+            }
+            None => (Records::Lines(text.lines().enumerate()), text.len()),
+        };
+        Self {
+            records,
+            total,
+            read: 0,
+            fields: vec![],
+            errors: vec![],
+        }
+    }
+
+    /// Reads up to `records` more records, returning whether the JSON is read to its end.
+    pub fn read(&mut self, records: usize) -> bool {
+        for _ in 0..records {
+            let (line, record) = match &mut self.records {
+                Records::Lines(lines) => {
+                    let Some((i, line)) = lines.next() else {
+                        return true;
+                    };
+                    self.read += line.len() + 1;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let record =
+                        serde_json::from_str(&quote_non_finite(line)).map_err(|e| e.to_string());
+                    (i + 1, record)
+                }
+                Records::Document(array) => {
+                    let Some((i, value)) = array.next() else {
+                        return true;
+                    };
+                    self.read += 1;
+                    (i + 1, Ok(value))
+                }
+            };
+            match record {
+                Ok(Value::Object(object)) => {
+                    let mut fields = vec![];
+                    flatten("", &Value::Object(object), &mut fields);
+                    self.fields.push((line, fields));
+                }
+                Ok(_) => self.errors.push(RowError {
+                    line,
+                    message: "not a JSON object".to_string(),
+                }),
+                Err(message) => self.errors.push(RowError { line, message }),
+            }
+        }
+        false
+    }
+
+    /// The fraction of the JSON read so far.
+    pub fn progress(&self) -> f64 {
+        match self.total {
+            0 => 1.0,
+            total => (self.read as f64 / total as f64).min(1.0),
+        }
+    }
+
+    pub fn finish(self, step_key: &str, metric_keys: &[String]) -> Result<Table, String> {
+        let records = self.fields;
+        let errors = self.errors;
+        if records.is_empty() {
+            return Err("no JSON records found".to_string());
+        }
+
+        // paths by the record they first appear in, sorted within it as serde_json sorts keys
+        let mut paths: Vec<&str> = vec![];
+        let mut seen = HashSet::new();
+        for (_, fields) in &records {
+            for (path, _) in fields {
+                if seen.insert(path.as_str()) {
+                    paths.push(path);
+                }
+            }
+        }
+        let step = match step_key.trim() {
+            "" => STEP_KEYS.into_iter().find(|key| paths.contains(key)),
+            key if paths.contains(&key) => Some(key),
+            key => return Err(format!("no record has the step key `{key}`")),
+        };
+        let metrics = match metric_keys.is_empty() {
+            true => paths
+                .iter()
+                .copied()
+                .filter(|&path| Some(path) != step)
+                .collect::<Vec<_>>(),
+            false => metric_keys.iter().map(String::as_str).collect(),
+        };
+
+        let rows = records
+            .iter()
+            .enumerate()
+            .map(|(i, (line, fields))| {
+                let fields = fields
+                    .iter()
+                    .map(|(path, value)| (path.as_str(), value.as_str()))
+                    .collect::<HashMap<_, _>>();
+                let value = |path: &str| fields.get(path).copied().unwrap_or_default().to_string();
+                let step = match step {
+                    Some(step) => value(step),
+                    None => (i + 1).to_string(),
+                };
+                let cells = std::iter::once(step)
+                    .chain(metrics.iter().map(|metric| value(metric)))
+                    .collect();
+                (*line, cells)
+            })
+            .collect();
+
+        Ok(Table {
+            headers: std::iter::once(step.unwrap_or("step"))
+                .chain(metrics.iter().copied())
+                .map(str::to_string)
+                .collect(),
+            has_header: true,
+            rows,
+            errors,
+        })
+    }
+}
+
+/// The key of the array of records an object holds, as W&B exports do.
+fn records_key(object: &serde_json::Map<String, Value>) -> Option<String> {
+    object.iter().find_map(|(key, value)| match value {
+        Value::Array(array) if array.iter().all(Value::is_object) => Some(key.clone()),
+        _ => None,
+    })
+}
+
+/// Collects the numeric leaves of `value` under their dotted paths.
+fn flatten(prefix: &str, value: &Value, fields: &mut Vec<(String, String)>) {
+    let path = |key: &str| match prefix.is_empty() {
+        true => key.to_string(),
+        false => format!("{prefix}.{key}"),
+    };
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                flatten(&path(key), value, fields);
+            }
+        }
+        Value::Array(array) => {
+            for (i, value) in array.iter().enumerate() {
+                flatten(&path(&i.to_string()), value, fields);
+            }
+        }
+        Value::Number(number) => fields.push((prefix.to_string(), number.to_string())),
+        Value::String(string) if matches!(string.as_str(), "NaN" | "Infinity" | "-Infinity") => {
+            fields.push((prefix.to_string(), string.clone()));
+        }
+        _ => {}
+    }
+}
+
+/// Turns the bare `NaN`, `Infinity` and `-Infinity` tokens of Python's `json` into strings.
+fn quote_non_finite(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if in_string {
+            match (escaped, c) {
+                (false, '\\') => escaped = true,
+                (false, '"') => in_string = false,
+                _ => escaped = false,
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if let Some(token) = ["NaN", "-Infinity", "Infinity"]
+            .into_iter()
+            .find(|token| rest.starts_with(token))
+        {
+            quoted.push('"');
+            quoted.push_str(token);
+            quoted.push('"');
+            rest = &rest[token.len()..];
+            continue;
+        }
+        quoted.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    quoted
+}
@@ -0,0 +1,165 @@
+use super::table::Table;
+use regex::Regex;
+
+/// A number as printed by Python loggers, including `nan` and `inf`.
+const NUM: &str = r"[-+]?(?:\d+\.?\d*|\.\d+)(?:[eE][-+]?\d+)?|[-+]?(?i:nan|inf)";
+
+/// Training frameworks whose stdout logs can be read without a custom pattern.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LogExtractor {
+    Megatron,
+    DeepSpeed,
+    HfTrainer,
+    PaddleNLP,
+}
+
+impl LogExtractor {
+    pub const ALL: [LogExtractor; 4] = [
+        Self::Megatron,
+        Self::DeepSpeed,
+        Self::HfTrainer,
+        Self::PaddleNLP,
+    ];
+
+    /// Pattern with a named capture per column, `step` is optional.
+    pub fn pattern(&self) -> String {
+        match self {
+            // iteration      100/    5000 | ... | learning rate: 1.000E-04 | ... | lm loss: 2.345E+00 | ... | grad norm: 1.234 |
+            Self::Megatron => format!(
+                r"iteration\s+(?P<step>\d+)\s*/\s*\d+(?:.*?learning rate:\s*(?P<lr>{NUM}))?.*?lm loss:\s*(?P<loss>{NUM})(?:.*?grad norm:\s*(?P<grad_norm>{NUM}))?"
+            ),
+            // steps: 100 loss: 2.3456 iter time (s): 0.512 samples/sec: 62.5
+            Self::DeepSpeed => format!(r"steps:\s*(?P<step>\d+)\s+loss:\s*(?P<loss>{NUM})"),
+            // {'loss': 2.345, 'grad_norm': 1.23, 'learning_rate': 5e-05, 'epoch': 0.1}
+            Self::HfTrainer => format!(
+                r"\{{'loss':\s*(?P<loss>{NUM})(?:,\s*'grad_norm':\s*(?P<grad_norm>{NUM}))?(?:,\s*'learning_rate':\s*(?P<lr>{NUM}))?"
+            ),
+            // loss: 2.30485725, learning_rate: 3e-05, global_step: 10, interval_runtime: 2.1
+            Self::PaddleNLP => format!(
+                r"loss:\s*(?P<loss>{NUM}),\s*learning_rate:\s*(?P<lr>{NUM}),\s*global_step:\s*(?P<step>\d+)"
+            ),
+        }
+    }
+
+    pub fn regex(&self) -> Regex {
+        Regex::new(&self.pattern()).expect("built-in log patterns are valid")
+    }
+
+    /// The extractor matching most lines of `text`, with the table it extracts.
+    pub fn detect(text: &str) -> Option<(LogExtractor, Table)> {
+        Self::ALL
+            .into_iter()
+            .filter_map(|extractor| Some((extractor, parse_log(text, &extractor.regex()).ok()?)))
+            .max_by_key(|(_, table)| table.rows.len())
+    }
+}
+
+impl std::fmt::Display for LogExtractor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Megatron => "Megatron",
+            Self::DeepSpeed => "DeepSpeed",
+            Self::HfTrainer => "HF Trainer",
+            Self::PaddleNLP => "PaddleNLP",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Compiles a user pattern, which needs at least one named capture besides `step`.
+pub fn custom_regex(pattern: &str) -> Result<Regex, String> {
+    let regex = Regex::new(pattern).map_err(|e| e.to_string())?;
+    match regex.capture_names().flatten().any(|name| name != "step") {
+        true => Ok(regex),
+        false => Err("the pattern needs a named capture such as (?P<loss>...)".to_string()),
+    }
+}
+
+/// Extracts one row per matching line, with a column per named capture of `regex`.
+///
+/// Captures that did not take part in a match are left empty. Without a `step` capture the
+/// steps count the matching lines, starting from 1.
+pub fn parse_log(text: &str, regex: &Regex) -> Result<Table, String> {
+    let mut reader = LogReader::new(text, regex.clone());
+    reader.read(usize::MAX);
+    reader.finish()
+}
+
+/// Reads a log a few lines at a time, see [`parse_log`].
+pub struct LogReader<'a> {
+    text: &'a str,
+    lines: std::iter::Enumerate<std::str::Lines<'a>>,
+    regex: Regex,
+    metrics: Vec<String>,
+    rows: Vec<(usize, Vec<String>)>,
+    /// Bytes of the lines read so far, about as line endings may be `\r\n`.
+    read: usize,
+}
+
+impl<'a> LogReader<'a> {
+    pub fn new(text: &'a str, regex: Regex) -> Self {
+        let metrics = regex
+            .capture_names()
+            .flatten()
+            .filter(|&name| name != "step")
+            .map(str::to_string)
+            .collect();
+        Self {
+            text,
+            lines: text.lines().enumerate(),
+            regex,
+            metrics,
+            rows: vec![],
+            read: 0,
+        }
+    }
+
+    /// Reads up to `lines` more lines, returning whether the log is read to its end.
+    pub fn read(&mut self, lines: usize) -> bool {
+        for _ in 0..lines {
+            let Some((i, line)) = self.lines.next() else {
+                return true;
+            };
+            self.read += line.len() + 1;
+            let Some(captures) = self.regex.captures(line) else {
+                continue;
+            };
+            let step = match captures.name("step") {
+                Some(step) => step.as_str().to_string(),
+                None => (self.rows.len() + 1).to_string(),
+            };
+            let cells = std::iter::once(step)
+                .chain(self.metrics.iter().map(|name| {
+                    captures
+                        .name(name)
+                        .map(|m| m.as_str().to_string())
+                        .unwrap_or_default()
+                }))
+                .collect();
+            self.rows.push((i + 1, cells));
+        }
+        false
+    }
+
+    /// The fraction of the log read so far.
+    pub fn progress(&self) -> f64 {
+        match self.text.is_empty() {
+            true => 1.0,
+            false => (self.read as f64 / self.text.len() as f64).min(1.0),
+        }
+    }
+
+    pub fn finish(self) -> Result<Table, String> {
+        if self.rows.is_empty() {
+            return Err("no line matches the pattern".to_string());
+        }
+        Ok(Table {
+            headers: std::iter::once("step".to_string())
+                .chain(self.metrics)
+                .collect(),
+            has_header: true,
+            rows: self.rows,
+            errors: vec![],
+        })
+    }
+}
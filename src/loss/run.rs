@@ -0,0 +1,198 @@
+use super::table::{BadRowPolicy, ColumnMapping, RowError, Table};
+use super::{table_to_loss, Loss};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Line colours handed out to runs in load order.
+const PALETTE: [&str; 8] = [
+    "#e66956", "#21a675", "#3b82f6", "#f59e0b", "#8b5cf6", "#06b6d4", "#ec4899", "#64748b",
+];
+
+/// One loaded file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Run {
+    pub id: Uuid,
+    pub name: String,
+    /// Hex colour, `#rrggbb`.
+    pub colour: String,
+    pub visible: bool,
+    pub table: Table,
+    pub mapping: ColumnMapping,
+}
+
+impl Run {
+    /// A visible run named after its file, with the column mapping guessed from the headers.
+    pub fn new(file_name: &str, table: Table, index: usize) -> Self {
+        let name = match file_name.rsplit_once('.') {
+            Some((stem, _)) if !stem.is_empty() => stem,
+            _ => file_name,
+        };
+        Self {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            colour: PALETTE[index % PALETTE.len()].to_string(),
+            visible: true,
+            mapping: table.detect_mapping(),
+            table,
+        }
+    }
+
+    /// The plotted series of this run.
+    ///
+    /// The first series takes the colour of the run, further ones the next palette colours.
+    pub fn series(&self) -> Vec<SeriesInfo> {
+        let single = self.mapping.series.len() == 1;
+        let offset = PALETTE
+            .iter()
+            .position(|&colour| colour == self.colour)
+            .unwrap_or(0);
+        self.mapping
+            .series
+            .iter()
+            .enumerate()
+            .map(|(i, &column)| SeriesInfo {
+                key: SeriesKey {
+                    run: self.id,
+                    column,
+                },
+                name: match single {
+                    true => self.name.clone(),
+                    false => format!("{}/{}", self.name, self.table.header(column)),
+                },
+                colour: match i {
+                    0 => self.colour.clone(),
+                    _ => PALETTE[(offset + i) % PALETTE.len()].to_string(),
+                },
+            })
+            .collect()
+    }
+
+    /// The baseline-like series of this run, or else its first series.
+    pub fn reference(&self) -> Option<SeriesKey> {
+        let column = self
+            .table
+            .reference_column(&self.mapping.series)
+            .or_else(|| self.mapping.series.first().copied())?;
+        Some(SeriesKey {
+            run: self.id,
+            column,
+        })
+    }
+}
+
+pub fn find_run(runs: &[Run], id: Uuid) -> Option<&Run> {
+    runs.iter().find(|run| run.id == id)
+}
+
+/// A series is a mapped column of a run.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SeriesKey {
+    pub run: Uuid,
+    pub column: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeriesInfo {
+    pub key: SeriesKey,
+    pub name: String,
+    /// Hex colour, `#rrggbb`.
+    pub colour: String,
+}
+
+/// How the rows of several runs are matched up by their step values.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Join {
+    /// Only steps logged by every run.
+    Inner,
+    /// Every step logged by any run, with gaps where a run did not log it.
+    #[default]
+    Outer,
+    /// Every step logged by any run, interpolating runs linearly between the steps they
+    /// logged.
+    Interpolated,
+}
+
+impl Join {
+    pub const ALL: [Join; 3] = [Self::Inner, Self::Outer, Self::Interpolated];
+}
+
+impl std::fmt::Display for Join {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Inner => "Inner",
+            Self::Outer => "Outer",
+            Self::Interpolated => "Interpolated",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The series of all visible runs, together with their rows aligned by step.
+///
+/// Bad rows are reported per run.
+#[allow(clippy::type_complexity)]
+pub fn runs_to_loss(
+    runs: &[Run],
+    policy: BadRowPolicy,
+    join: Join,
+) -> (Vec<SeriesInfo>, Vec<Loss>, Vec<(Uuid, Vec<RowError>)>) {
+    let mut series = vec![];
+    let mut errors = vec![];
+    let mut columns: Vec<(usize, Vec<Loss>)> = vec![];
+    for run in runs.iter().filter(|run| run.visible) {
+        let (mut loss, run_errors) = table_to_loss(&run.table, &run.mapping, policy);
+        // sorted by step, a step logged twice keeps its last value
+        loss.reverse();
+        loss.sort_by(|a, b| a.step.total_cmp(&b.step));
+        loss.dedup_by(|a, b| a.step == b.step);
+        series.extend(run.series());
+        errors.push((run.id, run_errors));
+        columns.push((run.mapping.series.len(), loss));
+    }
+
+    let mut steps = columns
+        .iter()
+        .flat_map(|(_, rows)| rows.iter().map(|row| row.step))
+        .collect::<Vec<_>>();
+    steps.sort_by(f64::total_cmp);
+    steps.dedup();
+    if join == Join::Inner {
+        steps.retain(|&step| {
+            columns
+                .iter()
+                .all(|(_, rows)| find_step(rows, step).is_ok())
+        });
+    }
+
+    let loss = steps
+        .into_iter()
+        .map(|step| {
+            let values = columns
+                .iter()
+                .flat_map(|(width, rows)| match (find_step(rows, step), join) {
+                    (Ok(i), _) => rows[i].values.clone(),
+                    (Err(i), Join::Interpolated) if i > 0 && i < rows.len() => {
+                        let (prev, next) = (&rows[i - 1], &rows[i]);
+                        let t = (step - prev.step) / (next.step - prev.step);
+                        prev.values
+                            .iter()
+                            .zip(&next.values)
+                            .map(|(a, b)| Some(a.as_ref()? + (b.as_ref()? - a.as_ref()?) * t))
+                            .collect()
+                    }
+                    (Err(_), _) => vec![None; *width],
+                })
+                .collect();
+            Loss {
+                step,
+                values,
+                smoothed: vec![],
+            }
+        })
+        .collect();
+    (series, loss, errors)
+}
+
+fn find_step(rows: &[Loss], step: f64) -> Result<usize, usize> {
+    rows.binary_search_by(|row| row.step.total_cmp(&step))
+}
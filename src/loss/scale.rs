@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::LN_10;
+
+/// How values are mapped onto the y axis of a chart.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Scale {
+    #[default]
+    Linear,
+    /// Base 10 logarithm, only positive values can be shown.
+    Log,
+    /// Linear around zero and logarithmic beyond, for signed values such as diffs.
+    SymLog,
+}
+
+impl Scale {
+    pub const ALL: [Scale; 3] = [Self::Linear, Self::Log, Self::SymLog];
+
+    /// Position of `value` on the axis, NaN for values a log axis cannot show.
+    ///
+    /// `linear_width` is how far from zero a symmetric log axis stays about linear.
+    pub fn apply(self, value: f64, linear_width: f64) -> f64 {
+        match self {
+            Self::Linear => value,
+            Self::Log => match value > 0.0 {
+                true => value.log10(),
+                false => f64::NAN,
+            },
+            Self::SymLog => value.signum() * (value.abs() / linear_width).ln_1p() / LN_10,
+        }
+    }
+
+    /// The value at `position` on the axis, the inverse of [`Scale::apply`].
+    pub fn invert(self, position: f64, linear_width: f64) -> f64 {
+        match self {
+            Self::Linear => position,
+            Self::Log => 10f64.powf(position),
+            Self::SymLog => position.signum() * linear_width * (position.abs() * LN_10).exp_m1(),
+        }
+    }
+}
+
+impl std::fmt::Display for Scale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Linear => "Linear",
+            Self::Log => "Log",
+            Self::SymLog => "Symmetric Log",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A short label for a tick, in scientific notation when it is very small or large.
+pub fn format_tick(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    match (1e-3..1e4).contains(&value.abs()) {
+        true => {
+            let label = format!("{value:.4}");
+            label
+                .trim_end_matches('0')
+                .trim_end_matches('.')
+                .to_string()
+        }
+        false => format!("{value:.1e}"),
+    }
+}
@@ -0,0 +1,164 @@
+use super::tolerance::Rule;
+use super::{calculate_feature, Feature, LossDiff};
+use serde::{Deserialize, Serialize};
+
+/// How the steps are split up for the per-segment statistics.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Segmentation {
+    /// Consecutive windows of a fixed number of steps.
+    #[default]
+    Windows,
+    /// Named step ranges given by the user.
+    Phases,
+}
+
+impl Segmentation {
+    pub const ALL: [Segmentation; 2] = [Self::Windows, Self::Phases];
+}
+
+impl std::fmt::Display for Segmentation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Windows => "Windows",
+            Self::Phases => "Phases",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A range of steps, `start` included and `end` excluded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Segment {
+    pub name: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// The statistics of the diffs within a segment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SegmentFeature {
+    pub segment: Segment,
+    pub feature: Feature,
+    /// Whether any tolerance rule is exceeded within the segment.
+    pub exceeded: bool,
+}
+
+/// Consecutive windows of `size` steps starting at the first step, leaving out empty ones.
+pub fn windows(diffs: &[LossDiff], size: f64) -> Vec<Segment> {
+    let Some(first) = diffs.first() else {
+        return vec![];
+    };
+    if size.is_nan() || size <= 0.0 {
+        return vec![];
+    }
+    let mut windows: Vec<Segment> = vec![];
+    for diff in diffs {
+        let start = first.step + ((diff.step - first.step) / size).floor() * size;
+        if windows.last().map(|window| window.start) != Some(start) {
+            windows.push(Segment {
+                name: format!("{start}-{}", start + size),
+                start,
+                end: start + size,
+            });
+        }
+    }
+    windows
+}
+
+/// Reads phases such as `warmup: 0-1000, steady: 1000-`, one per comma or line.
+///
+/// A missing bound leaves the phase open on that side, a missing name names it after its range.
+pub fn parse_phases(text: &str) -> Result<Vec<Segment>, String> {
+    text.split([',', '\n'])
+        .map(str::trim)
+        .filter(|phase| !phase.is_empty())
+        .map(|phase| {
+            let (name, range) = match phase.split_once(':') {
+                Some((name, range)) => (name.trim().to_string(), range.trim()),
+                None => (phase.to_string(), phase),
+            };
+            let (start, end) = range
+                .split_once('-')
+                .ok_or_else(|| format!("phase `{phase}` needs a range such as 0-1000"))?;
+            let bound = |bound: &str, open: f64| match bound.trim() {
+                "" => Ok(open),
+                bound => bound
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid step `{bound}` in phase `{phase}`")),
+            };
+            Ok(Segment {
+                name,
+                start: bound(start, f64::NEG_INFINITY)?,
+                end: bound(end, f64::INFINITY)?,
+            })
+        })
+        .collect()
+}
+
+/// The statistics of the `series`-th diff series within every segment, checked against
+/// `rules`.
+pub fn segment_features(
+    diffs: &[LossDiff],
+    series: usize,
+    segments: &[Segment],
+    rules: &[Rule],
+) -> Vec<SegmentFeature> {
+    segments
+        .iter()
+        .map(|segment| {
+            // the diffs are sorted by step
+            let start = diffs.partition_point(|diff| diff.step < segment.start);
+            let end = diffs.partition_point(|diff| diff.step < segment.end);
+            let diffs = &diffs[start..end.max(start)];
+            SegmentFeature {
+                segment: segment.clone(),
+                feature: calculate_feature(diffs, series),
+                exceeded: rules
+                    .iter()
+                    .any(|rule| rule.check(diffs, series).exceeded()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loss::tolerance::Metric;
+
+    fn diffs(abs: &[f64]) -> Vec<LossDiff> {
+        abs.iter()
+            .enumerate()
+            .map(|(i, abs)| LossDiff {
+                step: (i + 1) as f64,
+                abs: vec![Some(*abs)],
+                rel: vec![Some(abs / 2.0)],
+                base: Some(2.0),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nan_window_is_exceeded() {
+        let diffs = diffs(&[0.01, 0.02, 0.01, 0.02, f64::NAN, 0.01]);
+        let rules = [Rule::new(Metric::MaxAbs, 0.1, 0.0)];
+        let segments = windows(&diffs, 2.0);
+        let exceeded = segment_features(&diffs, 0, &segments, &rules)
+            .iter()
+            .map(|segment| segment.exceeded)
+            .collect::<Vec<_>>();
+        assert_eq!(exceeded, [false, false, true]);
+    }
+
+    #[test]
+    fn inf_window_is_exceeded() {
+        let diffs = diffs(&[0.01, f64::INFINITY, 0.01, 0.02]);
+        let rules = [Rule::new(Metric::MeanAbsRel, 0.1, 0.0)];
+        let segments = windows(&diffs, 2.0);
+        let exceeded = segment_features(&diffs, 0, &segments, &rules)
+            .iter()
+            .map(|segment| segment.exceeded)
+            .collect::<Vec<_>>();
+        assert_eq!(exceeded, [true, false]);
+    }
+}
@@ -0,0 +1,103 @@
+use super::Loss;
+use serde::{Deserialize, Serialize};
+
+/// How the loss curves are smoothed before plotting.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Smoothing {
+    #[default]
+    None,
+    /// Exponential moving average, debiased like TensorBoard.
+    Ema,
+    /// Mean of the trailing window.
+    MovingAverage,
+    /// Median of the trailing window.
+    Median,
+}
+
+impl Smoothing {
+    pub const ALL: [Smoothing; 4] = [Self::None, Self::Ema, Self::MovingAverage, Self::Median];
+
+    /// The largest EMA weight, at 1 the average never moves off its start.
+    pub const MAX_WEIGHT: f64 = 0.999;
+
+    /// Smooths one series, non-finite values are passed through and skipped by their
+    /// neighbours.
+    ///
+    /// `weight` is clamped to `[0, MAX_WEIGHT]`.
+    pub fn apply(&self, values: &[f64], weight: f64, window: usize) -> Vec<f64> {
+        let weight = match weight.is_nan() {
+            true => 0.0,
+            false => weight.clamp(0.0, Self::MAX_WEIGHT),
+        };
+        let window = window.max(1);
+        let mut last = 0.0;
+        let mut count = 0;
+        let mut recent = std::collections::VecDeque::with_capacity(window);
+        values
+            .iter()
+            .map(|&value| {
+                if !value.is_finite() {
+                    return value;
+                }
+                match self {
+                    Self::None => value,
+                    Self::Ema => {
+                        // the EMA starts at 0, dividing by 1 - weight^n removes that bias
+                        last = last * weight + (1.0 - weight) * value;
+                        count += 1;
+                        last / (1.0 - weight.powi(count))
+                    }
+                    Self::MovingAverage | Self::Median => {
+                        if recent.len() == window {
+                            recent.pop_front();
+                        }
+                        recent.push_back(value);
+                        match self {
+                            Self::MovingAverage => recent.iter().sum::<f64>() / recent.len() as f64,
+                            _ => {
+                                let mut sorted = recent.iter().copied().collect::<Vec<_>>();
+                                sorted.sort_by(f64::total_cmp);
+                                let mid = sorted.len() / 2;
+                                match sorted.len() % 2 {
+                                    0 => (sorted[mid - 1] + sorted[mid]) / 2.0,
+                                    _ => sorted[mid],
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Smoothing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::None => "None",
+            Self::Ema => "EMA",
+            Self::MovingAverage => "Moving Average",
+            Self::Median => "Median",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Fills in the `smoothed` values of every row, series by series.
+pub fn smooth(loss: &mut [Loss], smoothing: Smoothing, weight: f64, window: usize) {
+    let series = loss.first().map_or(0, |row| row.values.len());
+    for row in loss.iter_mut() {
+        row.smoothed = vec![None; series];
+    }
+    for i in 0..series {
+        let values = loss
+            .iter()
+            .map(|row| row.values.get(i).copied().flatten().unwrap_or(f64::NAN))
+            .collect::<Vec<_>>();
+        let smoothed = smoothing.apply(&values, weight, window);
+        // missing values stay missing
+        for (row, value) in loss.iter_mut().zip(smoothed) {
+            row.smoothed[i] = row.values.get(i).copied().flatten().map(|_| value);
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+
+/// A delimited text file with one column per metric.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub has_header: bool,
+    /// Cells of every record, keyed by the 1-based line number in the file.
+    pub rows: Vec<(usize, Vec<String>)>,
+    /// Records that could not be split into cells at all.
+    pub errors: Vec<RowError>,
+}
+
+/// Which column holds the step axis and which columns are plotted.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    pub step: usize,
+    pub series: Vec<usize>,
+}
+
+/// What to do with rows whose mapped cells are not numbers.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BadRowPolicy {
+    /// Drop the row.
+    #[default]
+    Skip,
+    /// Keep the row, plotting its bad cells as gaps.
+    Flag,
+    /// Load nothing when any row is bad.
+    Reject,
+}
+
+impl BadRowPolicy {
+    pub const ALL: [BadRowPolicy; 3] = [Self::Skip, Self::Flag, Self::Reject];
+}
+
+impl std::fmt::Display for BadRowPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Skip => "Skip",
+            Self::Flag => "Flag",
+            Self::Reject => "Reject",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A cell that could not be read, with the 1-based line number of its row.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RowError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for RowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Table {
+    /// Parses comma or tab separated text.
+    ///
+    /// The first record is taken as the header when any of its cells is not a number.
+    /// Headerless files get the legacy `step,xpu,gpu` header.
+    pub fn parse(text: &str) -> Self {
+        let mut reader = TableReader::new(text);
+        reader.read(usize::MAX);
+        reader.finish()
+    }
+
+    fn find_column(&self, names: &[&str]) -> Option<usize> {
+        self.headers
+            .iter()
+            .position(|header| names.contains(&header.to_ascii_lowercase().as_str()))
+    }
+
+    /// Guesses the step column from the header names.
+    ///
+    /// Loss columns are plotted when the header names some, e.g. next to the learning rate
+    /// extracted from a training log, otherwise every other column is.
+    pub fn detect_mapping(&self) -> ColumnMapping {
+        let step = self
+            .find_column(&["step", "steps", "iter", "iteration", "global_step"])
+            .unwrap_or(0);
+        let series = (0..self.headers.len())
+            .filter(|&column| column != step)
+            .collect::<Vec<_>>();
+        let losses = series
+            .iter()
+            .copied()
+            .filter(|&column| {
+                let header = self.headers[column].to_ascii_lowercase();
+                header == "loss"
+                    || ["_loss", " loss", "/loss", ".loss"]
+                        .iter()
+                        .any(|suffix| header.ends_with(suffix))
+            })
+            .collect::<Vec<_>>();
+        ColumnMapping {
+            step,
+            series: match losses.is_empty() {
+                true => series,
+                false => losses,
+            },
+        }
+    }
+
+    /// A baseline-like column among `series`, such as `gpu` of legacy `step,xpu,gpu` files.
+    pub fn reference_column(&self, series: &[usize]) -> Option<usize> {
+        self.find_column(&["gpu", "baseline", "base", "ref", "reference"])
+            .filter(|column| series.contains(column))
+    }
+
+    pub fn header(&self, column: usize) -> String {
+        self.headers
+            .get(column)
+            .cloned()
+            .unwrap_or_else(|| format!("column {column}"))
+    }
+
+    /// The value of a cell, NaN and infinity included, or why it cannot be read.
+    pub fn value(&self, cells: &[String], column: usize) -> Result<f64, String> {
+        let name = self.header(column);
+        match cells.get(column) {
+            Some(cell) if !cell.is_empty() => cell
+                .parse::<f64>()
+                .map_err(|_| format!("cannot parse `{cell}` in column {name}")),
+            _ => Err(format!("missing value in column {name}")),
+        }
+    }
+
+    /// The finite value of a cell, or why it cannot be plotted.
+    pub fn finite_value(&self, cells: &[String], column: usize) -> Result<f64, String> {
+        match self.value(cells, column)? {
+            value if value.is_finite() => Ok(value),
+            _ => Err(format!(
+                "non-finite value `{}` in column {}",
+                cells[column],
+                self.header(column)
+            )),
+        }
+    }
+}
+
+/// Reads a table a few records at a time, see [`Table::parse`].
+pub struct TableReader<'a> {
+    text: &'a [u8],
+    reader: csv::Reader<&'a [u8]>,
+    rows: Vec<(usize, Vec<String>)>,
+    errors: Vec<RowError>,
+    /// Lines before `counted`, the reader does not count the blank lines it skips.
+    lines: usize,
+    counted: usize,
+}
+
+impl<'a> TableReader<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let first_line = text.lines().find(|line| !line.trim().is_empty());
+        let delimiter = match first_line {
+            Some(line) if !line.contains(',') && line.contains('\t') => b'\t',
+            _ => b',',
+        };
+        Self {
+            text: text.as_bytes(),
+            reader: csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .delimiter(delimiter)
+                .trim(csv::Trim::All)
+                .from_reader(text.as_bytes()),
+            rows: vec![],
+            errors: vec![],
+            lines: 0,
+            counted: 0,
+        }
+    }
+
+    /// Reads up to `records` more records, returning whether the text is read to its end.
+    pub fn read(&mut self, records: usize) -> bool {
+        let mut record = csv::StringRecord::new();
+        for _ in 0..records {
+            match self.reader.read_record(&mut record) {
+                Ok(false) => return true,
+                Ok(true) if record.iter().all(str::is_empty) => {}
+                Ok(true) => {
+                    let line = self.line_of(record.position());
+                    self.rows
+                        .push((line, record.iter().map(str::to_owned).collect::<Vec<_>>()));
+                }
+                Err(err) => {
+                    let line = self.line_of(err.position());
+                    self.errors.push(RowError {
+                        line,
+                        message: err.to_string(),
+                    });
+                }
+            }
+        }
+        self.reader.is_done()
+    }
+
+    /// The fraction of the text read so far.
+    pub fn progress(&self) -> f64 {
+        match self.text.is_empty() {
+            true => 1.0,
+            false => self.reader.position().byte() as f64 / self.text.len() as f64,
+        }
+    }
+
+    /// The 1-based line a record starts on.
+    ///
+    /// A record after blank lines is positioned at the first of them, so its line is counted
+    /// up to its first character.
+    fn line_of(&mut self, position: Option<&csv::Position>) -> usize {
+        let Some(position) = position else {
+            return 0;
+        };
+        let text = self.text;
+        let mut offset = (position.byte() as usize).min(text.len());
+        while offset < text.len() && matches!(text[offset], b'\r' | b'\n') {
+            offset += 1;
+        }
+        if offset >= self.counted {
+            self.lines += text[self.counted..offset]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count();
+            self.counted = offset;
+        }
+        self.lines + 1
+    }
+
+    pub fn finish(self) -> Table {
+        let mut rows = self.rows;
+        let has_header = rows.first().is_some_and(|(_, cells)| {
+            cells
+                .iter()
+                .any(|cell| !cell.is_empty() && cell.parse::<f64>().is_err())
+        });
+        let headers = match has_header {
+            true => rows.remove(0).1,
+            false => {
+                let columns = rows.iter().map(|(_, cells)| cells.len()).max().unwrap_or(0);
+                (0..columns)
+                    .map(|i| match i {
+                        0 => "step".to_string(),
+                        1 => "xpu".to_string(),
+                        2 => "gpu".to_string(),
+                        _ => format!("column {i}"),
+                    })
+                    .collect()
+            }
+        };
+
+        Table {
+            headers,
+            has_header,
+            rows,
+            errors: self.errors,
+        }
+    }
+}
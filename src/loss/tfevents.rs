@@ -0,0 +1,300 @@
+use super::table::Table;
+use std::collections::BTreeMap;
+
+/// A scalar summary of a TensorBoard event file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scalar {
+    pub tag: String,
+    pub step: i64,
+    /// The value as printed at the precision it was logged with.
+    pub value: String,
+}
+
+/// Reads the scalars of an `events.out.tfevents.*` file into a table with a column per tag.
+pub fn parse_events(bytes: &[u8]) -> Result<Table, String> {
+    let mut reader = EventReader::new(bytes);
+    reader.read(usize::MAX)?;
+    reader.finish()
+}
+
+/// Reads an event file a few records at a time, see [`parse_events`] and [`read_scalars`].
+pub struct EventReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    scalars: Vec<Scalar>,
+}
+
+impl<'a> EventReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+            scalars: vec![],
+        }
+    }
+
+    /// Reads up to `records` more records, returning whether the file is read to its end.
+    pub fn read(&mut self, records: usize) -> Result<bool, String> {
+        let bytes = self.bytes;
+        for _ in 0..records {
+            let offset = self.offset;
+            let Some((header, rest)) = bytes[offset..].split_first_chunk::<8>() else {
+                return Ok(true);
+            };
+            let Some((length_crc, rest)) = rest.split_first_chunk::<4>() else {
+                return Ok(true);
+            };
+            if masked_crc(header) != u32::from_le_bytes(*length_crc) {
+                return Err(format!("corrupted record length at offset {offset}"));
+            }
+            // a length beyond the address space, as on wasm32, cannot be read anyway
+            let Ok(length) = usize::try_from(u64::from_le_bytes(*header)) else {
+                return Err(format!("invalid record length at offset {offset}"));
+            };
+            let Some((data, data_crc)) = rest
+                .split_at_checked(length)
+                .and_then(|(data, rest)| Some((data, rest.first_chunk::<4>()?)))
+            else {
+                return Ok(true);
+            };
+            if masked_crc(data) != u32::from_le_bytes(*data_crc) {
+                return Err(format!("corrupted record data at offset {offset}"));
+            }
+            read_event(data, &mut self.scalars)
+                .map_err(|e| format!("invalid event at offset {offset}: {e}"))?;
+            self.offset += 12 + length + 4;
+        }
+        Ok(false)
+    }
+
+    /// The fraction of the file read so far.
+    pub fn progress(&self) -> f64 {
+        match self.bytes.is_empty() {
+            true => 1.0,
+            false => self.offset as f64 / self.bytes.len() as f64,
+        }
+    }
+
+    pub fn finish(self) -> Result<Table, String> {
+        scalars_to_table(&self.scalars)
+    }
+}
+
+/// A table with a column per tag and a row per step.
+fn scalars_to_table(scalars: &[Scalar]) -> Result<Table, String> {
+    if scalars.is_empty() {
+        return Err("the event file has no scalar summaries".to_string());
+    }
+
+    let mut tags: Vec<&str> = vec![];
+    let mut steps = BTreeMap::<i64, Vec<Option<&str>>>::new();
+    for scalar in scalars {
+        let column = match tags.iter().position(|&tag| tag == scalar.tag) {
+            Some(column) => column,
+            None => {
+                tags.push(&scalar.tag);
+                tags.len() - 1
+            }
+        };
+        let row = steps.entry(scalar.step).or_default();
+        if row.len() <= column {
+            row.resize(column + 1, None);
+        }
+        // a restarted run logs the same step again, the latest value wins
+        row[column] = Some(&scalar.value);
+    }
+
+    Ok(Table {
+        headers: std::iter::once("step")
+            .chain(tags.iter().copied())
+            .map(str::to_string)
+            .collect(),
+        has_header: true,
+        rows: steps
+            .into_iter()
+            .enumerate()
+            .map(|(i, (step, values))| {
+                let cells = std::iter::once(step.to_string())
+                    .chain((0..tags.len()).map(|column| {
+                        values
+                            .get(column)
+                            .copied()
+                            .flatten()
+                            .unwrap_or_default()
+                            .to_string()
+                    }))
+                    .collect();
+                (i + 1, cells)
+            })
+            .collect(),
+        errors: vec![],
+    })
+}
+
+/// Decodes the TFRecord framing and the scalar values of every `Event` record.
+///
+/// A record is a little-endian `u64` length, the masked CRC-32C of the length, the data and
+/// the masked CRC-32C of the data. A truncated last record, as left by a run that is still
+/// writing, ends the file.
+pub fn read_scalars(bytes: &[u8]) -> Result<Vec<Scalar>, String> {
+    let mut reader = EventReader::new(bytes);
+    reader.read(usize::MAX)?;
+    Ok(reader.scalars)
+}
+
+/// `Event { int64 step = 2; Summary summary = 5; }`
+fn read_event(data: &[u8], scalars: &mut Vec<Scalar>) -> Result<(), String> {
+    let mut step = 0;
+    let mut summary = None;
+    for (field, value) in fields(data)? {
+        match (field, value) {
+            (2, Wire::Varint(value)) => step = value as i64,
+            (5, Wire::Bytes(bytes)) => summary = Some(bytes),
+            _ => {}
+        }
+    }
+    let Some(summary) = summary else {
+        return Ok(());
+    };
+    // Summary { repeated Value value = 1; }
+    for (field, value) in fields(summary)? {
+        if let (1, Wire::Bytes(value)) = (field, value) {
+            if let Some((tag, value)) = read_value(value)? {
+                scalars.push(Scalar { tag, step, value });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `Value { string tag = 1; float simple_value = 2; TensorProto tensor = 8; }`
+///
+/// TF1 writes scalars as `simple_value`, TF2 and PyTorch as single element tensors.
+fn read_value(data: &[u8]) -> Result<Option<(String, String)>, String> {
+    let mut tag = None;
+    let mut value = None;
+    for (field, wire) in fields(data)? {
+        match (field, wire) {
+            (1, Wire::Bytes(bytes)) => tag = Some(String::from_utf8_lossy(bytes).into_owned()),
+            (2, Wire::Fixed32(bits)) => value = Some(f32::from_bits(bits).to_string()),
+            (8, Wire::Bytes(bytes)) => value = read_tensor(bytes)?,
+            _ => {}
+        }
+    }
+    Ok(tag.zip(value))
+}
+
+/// The value of a single element `DT_FLOAT` or `DT_DOUBLE` `TensorProto`.
+fn read_tensor(data: &[u8]) -> Result<Option<String>, String> {
+    const DT_FLOAT: u64 = 1;
+    const DT_DOUBLE: u64 = 2;
+    let mut dtype = 0;
+    let mut values = vec![];
+    let mut content: &[u8] = &[];
+    for (field, wire) in fields(data)? {
+        match (field, wire) {
+            (1, Wire::Varint(value)) => dtype = value,
+            (4, Wire::Bytes(bytes)) => content = bytes,
+            // float_val, packed or not
+            (5, Wire::Fixed32(bits)) => values.push(f32::from_bits(bits).to_string()),
+            (5, Wire::Bytes(bytes)) => values.extend(
+                bytes
+                    .as_chunks::<4>()
+                    .0
+                    .iter()
+                    .map(|b| f32::from_bits(u32::from_le_bytes(*b)).to_string()),
+            ),
+            // double_val, packed or not
+            (6, Wire::Fixed64(bits)) => values.push(f64::from_bits(bits).to_string()),
+            (6, Wire::Bytes(bytes)) => values.extend(
+                bytes
+                    .as_chunks::<8>()
+                    .0
+                    .iter()
+                    .map(|b| f64::from_bits(u64::from_le_bytes(*b)).to_string()),
+            ),
+            _ => {}
+        }
+    }
+    match (dtype, content.len(), values.len()) {
+        (DT_FLOAT, 4, _) => Ok(content
+            .first_chunk::<4>()
+            .map(|bytes| f32::from_le_bytes(*bytes).to_string())),
+        (DT_DOUBLE, 8, _) => Ok(content
+            .first_chunk::<8>()
+            .map(|bytes| f64::from_le_bytes(*bytes).to_string())),
+        (DT_FLOAT | DT_DOUBLE, 0, 1) => Ok(values.pop()),
+        _ => Ok(None),
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Wire<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+/// Splits a protobuf message into its fields.
+fn fields(data: &[u8]) -> Result<Vec<(u64, Wire<'_>)>, String> {
+    let mut fields = vec![];
+    let mut pos = 0;
+    let truncated = || "truncated message".to_string();
+    while pos < data.len() {
+        let key = read_varint(data, &mut pos).ok_or_else(truncated)?;
+        let wire = match key & 7 {
+            0 => Wire::Varint(read_varint(data, &mut pos).ok_or_else(truncated)?),
+            1 => {
+                let bytes = data[pos..].first_chunk::<8>().ok_or_else(truncated)?;
+                pos += 8;
+                Wire::Fixed64(u64::from_le_bytes(*bytes))
+            }
+            2 => {
+                let len = read_varint(data, &mut pos).ok_or_else(truncated)?;
+                let len = usize::try_from(len).map_err(|_| truncated())?;
+                let bytes = data
+                    .get(pos..pos.saturating_add(len))
+                    .ok_or_else(truncated)?;
+                pos += len;
+                Wire::Bytes(bytes)
+            }
+            5 => {
+                let bytes = data[pos..].first_chunk::<4>().ok_or_else(truncated)?;
+                pos += 4;
+                Wire::Fixed32(u32::from_le_bytes(*bytes))
+            }
+            wire_type => return Err(format!("unsupported wire type {wire_type}")),
+        };
+        fields.push((key >> 3, wire));
+    }
+    Ok(fields)
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = *data.get(*pos)?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// CRC-32C (Castagnoli), masked the way TFRecord stores it.
+fn masked_crc(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0x82f6_3b78,
+                _ => crc >> 1,
+            };
+        }
+    }
+    (!crc).rotate_right(15).wrapping_add(0xa282_ead8)
+}
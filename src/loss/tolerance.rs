@@ -0,0 +1,210 @@
+use super::LossDiff;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// The per-step difference a tolerance rule bounds.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Metric {
+    MeanAbs,
+    MeanAbsRel,
+    MaxAbs,
+    MaxAbsRel,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 4] = [
+        Self::MeanAbs,
+        Self::MeanAbsRel,
+        Self::MaxAbs,
+        Self::MaxAbsRel,
+    ];
+
+    /// The absolute or absolute relative diff of the `series`-th diff series at a step.
+    fn value(&self, diff: &LossDiff, series: usize) -> Option<f64> {
+        match self {
+            Self::MeanAbs | Self::MaxAbs => diff.abs.get(series).copied().flatten().map(f64::abs),
+            Self::MeanAbsRel | Self::MaxAbsRel => {
+                diff.rel.get(series).copied().flatten().map(f64::abs)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::MeanAbs => "Mean Abs Diff",
+            Self::MeanAbsRel => "Mean Abs Rel Diff",
+            Self::MaxAbs => "Max Abs Diff",
+            Self::MaxAbsRel => "Max Abs Rel Diff",
+        };
+        f.write_str(name)
+    }
+}
+
+/// `metric` must stay below `threshold` from step `warmup` on.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub id: Uuid,
+    pub metric: Metric,
+    pub threshold: f64,
+    pub warmup: f64,
+}
+
+impl Rule {
+    pub fn new(metric: Metric, threshold: f64, warmup: f64) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            metric,
+            threshold,
+            warmup,
+        }
+    }
+
+    /// The rules suggested for a new comparison.
+    pub fn defaults() -> Vec<Rule> {
+        vec![
+            Self::new(Metric::MeanAbsRel, 0.01, 0.0),
+            Self::new(Metric::MaxAbs, 0.1, 100.0),
+        ]
+    }
+
+    /// Checks the `series`-th diff series.
+    ///
+    /// The first violation is the first step whose own diff reaches the threshold, for a mean
+    /// rule that is where the run starts drifting even if the mean stays below it. A NaN or
+    /// infinite diff from the warmup on always violates the rule, and is reported as the value.
+    pub fn check(&self, diffs: &[LossDiff], series: usize) -> Check {
+        let values = diffs
+            .iter()
+            .filter(|diff| diff.step >= self.warmup)
+            .filter_map(|diff| Some((diff.step, self.metric.value(diff, series)?)))
+            .collect::<Vec<_>>();
+        let first_violation = values
+            .iter()
+            .find(|(_, value)| !value.is_finite() || *value >= self.threshold)
+            .map(|(step, _)| *step);
+        if let Some((_, value)) = values.iter().find(|(_, value)| !value.is_finite()) {
+            return Check {
+                value: *value,
+                passed: false,
+                first_violation,
+            };
+        }
+        let value = match self.metric {
+            Metric::MeanAbs | Metric::MeanAbsRel => {
+                values.iter().map(|(_, value)| value).sum::<f64>() / values.len() as f64
+            }
+            Metric::MaxAbs | Metric::MaxAbsRel => values
+                .iter()
+                .map(|(_, value)| *value)
+                .fold(f64::NAN, f64::max),
+        };
+        Check {
+            value,
+            // nothing checked is no evidence of alignment
+            passed: !values.is_empty() && value < self.threshold,
+            first_violation,
+        }
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} < {}", self.metric, self.threshold)?;
+        if self.warmup > 0.0 {
+            write!(f, " from step {}", self.warmup)?;
+        }
+        Ok(())
+    }
+}
+
+/// The outcome of one rule for one diff series.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Check {
+    /// The mean or max of the checked steps, NaN when no step was checked, or the first NaN or
+    /// infinite diff.
+    pub value: f64,
+    pub passed: bool,
+    pub first_violation: Option<f64>,
+}
+
+impl Check {
+    /// Whether the checked steps went beyond the threshold or were not finite, unlike a failed
+    /// check this is false when no step was checked.
+    pub fn exceeded(&self) -> bool {
+        !self.passed && self.first_violation.is_some()
+    }
+}
+
+/// Checks every rule against the `series`-th diff series.
+pub fn verdict(rules: &[Rule], diffs: &[LossDiff], series: usize) -> Vec<(Rule, Check)> {
+    rules
+        .iter()
+        .map(|rule| (rule.clone(), rule.check(diffs, series)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diffs(abs: &[f64]) -> Vec<LossDiff> {
+        abs.iter()
+            .enumerate()
+            .map(|(i, abs)| LossDiff {
+                step: (i + 1) as f64,
+                abs: vec![Some(*abs)],
+                rel: vec![Some(abs / 2.0)],
+                base: Some(2.0),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finite_diffs_below_the_threshold_pass() {
+        let rule = Rule::new(Metric::MaxAbs, 0.1, 0.0);
+        let check = rule.check(&diffs(&[0.01, 0.02, 0.03]), 0);
+        assert!(check.passed);
+        assert_eq!(check.value, 0.03);
+        assert_eq!(check.first_violation, None);
+        assert!(!check.exceeded());
+    }
+
+    #[test]
+    fn nan_after_warmup_fails() {
+        for metric in Metric::ALL {
+            let rule = Rule::new(metric, 0.1, 2.0);
+            let check = rule.check(&diffs(&[0.01, 0.02, f64::NAN, 0.01]), 0);
+            assert!(!check.passed, "{metric}");
+            assert!(check.value.is_nan(), "{metric}");
+            assert_eq!(check.first_violation, Some(3.0), "{metric}");
+            assert!(check.exceeded(), "{metric}");
+        }
+    }
+
+    #[test]
+    fn inf_after_warmup_fails() {
+        let rule = Rule::new(Metric::MeanAbs, 0.1, 0.0);
+        let check = rule.check(&diffs(&[0.01, f64::NEG_INFINITY, 0.01]), 0);
+        assert!(!check.passed);
+        assert_eq!(check.value, f64::INFINITY);
+        assert_eq!(check.first_violation, Some(2.0));
+    }
+
+    #[test]
+    fn nan_during_warmup_is_ignored() {
+        let rule = Rule::new(Metric::MaxAbs, 0.1, 2.0);
+        let check = rule.check(&diffs(&[f64::NAN, 0.02, 0.03]), 0);
+        assert!(check.passed);
+        assert_eq!(check.first_violation, None);
+    }
+
+    #[test]
+    fn nothing_checked_fails_without_exceeding() {
+        let rule = Rule::new(Metric::MeanAbs, 0.1, 10.0);
+        let check = rule.check(&diffs(&[0.01, 0.02]), 0);
+        assert!(!check.passed);
+        assert!(!check.exceeded());
+    }
+}
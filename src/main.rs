@@ -1,15 +1,23 @@
 #[cfg(feature = "ssr")]
 #[tokio::main]
 async fn main() {
+    use axum::extract::DefaultBodyLimit;
     use axum::Router;
     use dotenv::dotenv;
     use leptos::*;
     use leptos_axum::{generate_route_list, LeptosRoutes};
     use llm_tools::app::*;
     use llm_tools::fileserv::file_and_error_handler;
+    use llm_tools::pages::MAX_RUN_BYTES;
     use tower_http::compression::CompressionLayer;
     use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
+    // `llm-tools compare <reference> <candidate>` checks two runs without starting the server
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.first().is_some_and(|arg| arg == "compare") {
+        std::process::exit(llm_tools::loss::compare::main(&args[1..]));
+    }
+
     dotenv().ok();
 
     // Parse an `EnvFilter` configuration from the `RUST_LOG`
@@ -45,6 +53,8 @@ async fn main() {
         let app = Router::new()
             .leptos_routes(&leptos_options, routes, App)
             .fallback(file_and_error_handler)
+            // shared runs are uploaded whole, past the default limit of 2 MB
+            .layer(DefaultBodyLimit::max(MAX_RUN_BYTES))
             .layer(
                 CompressionLayer::new()
                     .gzip(true)
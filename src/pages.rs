@@ -4,8 +4,8 @@ mod draw;
 mod error;
 mod home;
 
-pub use calculator::CalculatorPage;
+pub use calculator::{CalculatorPage, CalculatorReportPage};
 pub use chat::ChatPage;
-pub use draw::DrawPage;
+pub use draw::{DrawPage, MAX_RUN_BYTES};
 pub use error::{AppError, ErrorPage};
 pub use home::HomePage;
@@ -1,7 +1,28 @@
+mod calibration;
+mod checkpoint;
+mod memory;
+mod model;
+mod report;
+mod validation;
+
 use crate::components::*;
+use calibration::{
+    fit_overhead, load_calibrations, parse_memory_summary, save_calibration, Calibration,
+    MeasuredMemory,
+};
+use checkpoint::{
+    estimate_checkpoint, CheckpointEstimate, CheckpointFormat, CheckpointKind, CheckpointLayout,
+};
 use leptonic::{components::prelude::*, prelude::*};
 use leptos::*;
+use memory::{estimate_memory, MemoryBreakdown, MemoryCategory, MemoryConfig};
+use model::{SubModel, GIB};
+use report::{timestamp, Report, ReportConfig};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use validation::{validate, Field, FieldError};
+use wasm_bindgen::prelude::*;
+use web_sys::{Event, FileReader};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Model {
@@ -25,10 +46,54 @@ impl std::fmt::Display for Model {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 enum TrainMode {
     FP16,
     BF16,
+    FP32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Framework {
+    Megatron,
+    DeepSpeed,
+    Fsdp,
+    PaddleNLP,
+}
+
+impl Framework {
+    const ALL: [Framework; 4] = [Self::Megatron, Self::DeepSpeed, Self::Fsdp, Self::PaddleNLP];
+
+    /// The framework named in a measurement, by its display name or a word of it, e.g. `fsdp`.
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        Self::ALL.into_iter().find(|framework| {
+            let display = framework.to_string().to_lowercase();
+            display == name || display.split([' ', '-']).any(|word| word == name)
+        })
+    }
+}
+
+impl std::fmt::Display for Framework {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Megatron => "Megatron-LM",
+            Self::DeepSpeed => "DeepSpeed",
+            Self::Fsdp => "PyTorch FSDP",
+            Self::PaddleNLP => "PaddleNLP",
+        };
+        f.write_str(name)
+    }
+}
+
+impl TrainMode {
+    /// Bytes per element of the weights and gradients.
+    pub const fn bytes(self) -> f64 {
+        match self {
+            Self::FP16 | Self::BF16 => 2.0,
+            Self::FP32 => 4.0,
+        }
+    }
 }
 
 #[component]
@@ -44,9 +109,97 @@ pub fn CalculatorPage() -> impl IntoView {
     let (params, set_params) = create_signal(70.0);
     let (layers, set_layers) = create_signal(80.0);
     let (hidden_size, set_hidden_size) = create_signal(8192.0);
-    let (mem_useage, set_mem_useage) = create_signal(Option::<f64>::None);
+    let (heads, set_heads) = create_signal(64.0);
+    let (seq_len, set_seq_len) = create_signal(8192.0);
+    let (micro_batch, set_micro_batch) = create_signal(1.0);
+    let (trainable, set_trainable) = create_signal(true);
+    // Sub-models feeding the language model, e.g. a vision encoder and a projector.
+    let encoders = create_rw_signal(Vec::<SubModel>::new());
+    let (mem_useage, set_mem_useage) = create_signal(Option::<MemoryBreakdown>::None);
+
+    let (tp, set_tp) = create_signal(8.0);
+    let (dp, set_dp) = create_signal(8.0);
+    let (ckpt_kind, set_ckpt_kind) = create_signal(CheckpointKind::Full);
+    let (ckpt_layout, set_ckpt_layout) = create_signal(CheckpointLayout::Sharded);
+    let (ckpt_format, set_ckpt_format) = create_signal(CheckpointFormat::Torch);
+    let (bandwidth, set_bandwidth) = create_signal(2.0);
+    let (checkpoint, set_checkpoint) = create_signal(Option::<CheckpointEstimate>::None);
+
+    let (device, set_device) = create_signal("A100-80G".to_string());
+    let (framework, set_framework) = create_signal(Framework::Megatron);
+    let (measured, set_measured) = create_signal(Option::<MeasuredMemory>::None);
+    let save_action = create_action(|calibration: &Calibration| {
+        let calibration = calibration.clone();
+        save_calibration(calibration)
+    });
+    let calibrations = create_resource(
+        move || save_action.version().get(),
+        |_| async move { load_calibrations().await.unwrap_or_default() },
+    );
+    // The overhead factor saved for the selected device and framework.
+    let saved_factor = move || {
+        let device = device();
+        let framework = framework().to_string();
+        calibrations.get().and_then(|calibrations| {
+            calibrations
+                .into_iter()
+                .find(|c| c.device == device && c.framework == framework)
+                .map(|c| c.factor)
+        })
+    };
+    let fitted_factor = move || {
+        mem_useage()
+            .zip(measured())
+            .and_then(|(estimate, measured)| fit_overhead(&estimate, &measured))
+    };
+
+    // The language model always comes last, after the encoders feeding it.
+    let sub_models = move || {
+        let mut sub_models = encoders.get();
+        sub_models.push(SubModel {
+            id: Uuid::nil(),
+            name: model.get().to_string(),
+            params: params.get(),
+            layers: layers.get(),
+            hidden_size: hidden_size.get(),
+            heads: heads.get(),
+            seq_len: seq_len.get(),
+            tokens: 0.0,
+            dtype: train_mode.get(),
+            trainable: trainable.get(),
+        });
+        sub_models
+    };
+    let memory_config = move || MemoryConfig {
+        sub_models: sub_models(),
+        micro_batch: micro_batch.get(),
+        zero_level: zero_level.get(),
+        sp: sp.get(),
+        tp: tp.get(),
+        dp: dp.get(),
+    };
+    let errors = create_memo(move |_| validate(&memory_config(), bandwidth.get()));
+    let report_config = move || ReportConfig {
+        memory: memory_config(),
+        checkpoint_kind: ckpt_kind.get(),
+        checkpoint_layout: ckpt_layout.get(),
+        checkpoint_format: ckpt_format.get(),
+        bandwidth: bandwidth.get(),
+        calibration: saved_factor().map(|factor| Calibration {
+            device: device.get(),
+            framework: framework.get().to_string(),
+            factor,
+        }),
+    };
+    let open_print_view = move |_| {
+        let href = report_config().print_href(&timestamp());
+        _ = window().open_with_url_and_target(&href, "_blank");
+    };
 
     let calculate = move |_| {
+        if !errors.with_untracked(Vec::is_empty) {
+            return;
+        }
         toasts.push(Toast {
             id: Uuid::new_v4(),
             created_at: time::OffsetDateTime::now_utc(),
@@ -63,9 +216,90 @@ pub fn CalculatorPage() -> impl IntoView {
             .into_view(),
             timeout: ToastTimeout::DefaultDelay,
         });
-        set_mem_useage(Some(86.3689));
+        set_mem_useage(Some(estimate_memory(&memory_config())));
+        set_checkpoint(Some(estimate_checkpoint(&report_config().checkpoint())));
     };
 
+    let on_measured_change = move |ev: Event| {
+        let input = event_target::<web_sys::HtmlInputElement>(&ev);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        let file_reader = FileReader::new().unwrap();
+        let onloadend = Closure::wrap(Box::new(move |event: Event| {
+            let file_reader = event.target().unwrap().dyn_into::<FileReader>().unwrap();
+            let text = file_reader
+                .result()
+                .ok()
+                .and_then(|content| content.as_string())
+                .unwrap_or_default();
+            match parse_memory_summary(&text) {
+                Ok(summary) => {
+                    if let Some(device) = summary.device.clone() {
+                        set_device(device);
+                    }
+                    if let Some(name) = summary.framework.as_deref() {
+                        match Framework::from_name(name) {
+                            Some(framework) => set_framework(framework),
+                            None => toasts.push(Toast {
+                                id: Uuid::new_v4(),
+                                created_at: time::OffsetDateTime::now_utc(),
+                                variant: ToastVariant::Warn,
+                                header: "Unknown framework!".to_owned().into_view(),
+                                body: format!(
+                                    "{name} is not a known framework, keeping {}.",
+                                    framework.get_untracked()
+                                )
+                                .into_view(),
+                                timeout: ToastTimeout::CustomDelay(time::Duration::seconds(5)),
+                            }),
+                        }
+                    }
+                    set_measured(Some(summary));
+                }
+                Err(err) => {
+                    set_measured(None);
+                    toasts.push(Toast {
+                        id: Uuid::new_v4(),
+                        created_at: time::OffsetDateTime::now_utc(),
+                        variant: ToastVariant::Error,
+                        header: "Invalid memory summary!".to_owned().into_view(),
+                        body: err.into_view(),
+                        timeout: ToastTimeout::CustomDelay(time::Duration::seconds(5)),
+                    });
+                }
+            }
+        }) as Box<dyn FnMut(_)>);
+
+        file_reader.set_onloadend(Some(onloadend.as_ref().unchecked_ref()));
+        file_reader.read_as_text(&file).unwrap();
+        // prevent the callback from being dropped
+        onloadend.forget();
+    };
+
+    let save_factor = move |_| {
+        if let Some(factor) = fitted_factor() {
+            save_action.dispatch(Calibration {
+                device: device.get_untracked(),
+                framework: framework.get_untracked().to_string(),
+                factor,
+            });
+        }
+    };
+
+    create_effect(move |_| {
+        if let Some(Err(err)) = save_action.value().get() {
+            toasts.push(Toast {
+                id: Uuid::new_v4(),
+                created_at: time::OffsetDateTime::now_utc(),
+                variant: ToastVariant::Error,
+                header: "Failed to save the overhead factor!".to_owned().into_view(),
+                body: err.to_string().into_view(),
+                timeout: ToastTimeout::CustomDelay(time::Duration::seconds(5)),
+            });
+        }
+    });
+
     // Adjust model parameters when the model type changes.
     create_effect(move |_| match model() {
         Model::Llama2_7B => {
@@ -73,35 +307,50 @@ pub fn CalculatorPage() -> impl IntoView {
             set_layers(32.0);
             set_params(7.0);
             set_hidden_size(4096.0);
+            set_heads(32.0);
+            set_seq_len(4096.0);
             set_mem_useage(None);
+            set_checkpoint(None);
         }
         Model::Llama2_13B => {
             set_train_mode(TrainMode::FP16);
             set_layers(40.0);
             set_params(13.0);
             set_hidden_size(5120.0);
+            set_heads(40.0);
+            set_seq_len(4096.0);
             set_mem_useage(None);
+            set_checkpoint(None);
         }
         Model::Llama2_70B => {
             set_train_mode(TrainMode::FP16);
             set_layers(80.0);
             set_params(70.0);
             set_hidden_size(8192.0);
+            set_heads(64.0);
+            set_seq_len(4096.0);
             set_mem_useage(None);
+            set_checkpoint(None);
         }
         Model::Llama3_8B => {
             set_train_mode(TrainMode::BF16);
             set_layers(32.0);
             set_params(8.0);
             set_hidden_size(4096.0);
+            set_heads(32.0);
+            set_seq_len(8192.0);
             set_mem_useage(None);
+            set_checkpoint(None);
         }
         Model::Llama3_70B => {
             set_train_mode(TrainMode::BF16);
             set_layers(80.0);
             set_params(70.0);
             set_hidden_size(8192.0);
+            set_heads(64.0);
+            set_seq_len(8192.0);
             set_mem_useage(None);
+            set_checkpoint(None);
         }
     });
 
@@ -134,7 +383,7 @@ pub fn CalculatorPage() -> impl IntoView {
                         <FormControl class="flex flex-row">
                             <Label class="w-28 mr-1">"Train Mode"</Label>
                             <Select
-                                options=vec![TrainMode::FP16, TrainMode::BF16]
+                                options=vec![TrainMode::FP16, TrainMode::BF16, TrainMode::FP32]
 
                                 search_text_provider=move |option| format!("{:?}", option)
                                 render_option=move |option| format!("{:?}", option)
@@ -156,6 +405,7 @@ pub fn CalculatorPage() -> impl IntoView {
                             />
                             <Label class="ml-1">"B"</Label>
                         </FormControl>
+                        <ValidationMessage errors field=Field::Params/>
 
                         <FormControl class="flex flex-row">
                             <Label class="w-28 mr-1">"Layer Number"</Label>
@@ -168,6 +418,7 @@ pub fn CalculatorPage() -> impl IntoView {
                                 class="w-36"
                             />
                         </FormControl>
+                        <ValidationMessage errors field=Field::Layers/>
 
                         <FormControl class="flex flex-row">
                             <Label class="w-28 mr-1">"Hidden Size"</Label>
@@ -180,6 +431,51 @@ pub fn CalculatorPage() -> impl IntoView {
                                 class="w-36"
                             />
                         </FormControl>
+                        <ValidationMessage errors field=Field::HiddenSize/>
+
+                        <FormControl class="flex flex-row">
+                            <Label class="w-28 mr-1">"Attention Heads"</Label>
+                            <NumberInput
+                                min=1.0
+                                max=1024.0
+                                step=1.0
+                                get=heads
+                                set=set_heads
+                                class="w-36"
+                            />
+                        </FormControl>
+                        <ValidationMessage errors field=Field::Heads/>
+
+                        <FormControl class="flex flex-row">
+                            <Label class="w-28 mr-1">"Sequence Length"</Label>
+                            <NumberInput
+                                min=1.0
+                                max=1048576.0
+                                step=1.0
+                                get=seq_len
+                                set=set_seq_len
+                                class="w-36"
+                            />
+                        </FormControl>
+                        <ValidationMessage errors field=Field::SeqLen/>
+
+                        <FormControl class="flex flex-row">
+                            <Label class="w-28 mr-1">"Micro Batch"</Label>
+                            <NumberInput
+                                min=1.0
+                                max=1024.0
+                                step=1.0
+                                get=micro_batch
+                                set=set_micro_batch
+                                class="w-36"
+                            />
+                        </FormControl>
+                        <ValidationMessage errors field=Field::MicroBatch/>
+
+                        <FormControl class="flex flex-row">
+                            <Checkbox checked=trainable set_checked=set_trainable/>
+                            <Label class="ml-1">"Trainable"</Label>
+                        </FormControl>
                     </div>
                 </Col>
 
@@ -231,6 +527,129 @@ pub fn CalculatorPage() -> impl IntoView {
                                 <Label class="ml-1">"Zero-3"</Label>
                             </FormControl>
                         </RadioGroup>
+
+                        <FormControl class="flex flex-row">
+                            <Label class="w-28 mr-1">"TP Size"</Label>
+                            <NumberInput
+                                min=1.0
+                                max=1024.0
+                                step=1.0
+                                get=tp
+                                set=set_tp
+                                class="w-36"
+                            />
+                        </FormControl>
+                        <ValidationMessage errors field=Field::Tp/>
+
+                        <FormControl class="flex flex-row">
+                            <Label class="w-28 mr-1">"DP Size"</Label>
+                            <NumberInput
+                                min=1.0
+                                max=65536.0
+                                step=1.0
+                                get=dp
+                                set=set_dp
+                                class="w-36"
+                            />
+                        </FormControl>
+                        <ValidationMessage errors field=Field::Dp/>
+                    </div>
+                </Col>
+            </Row>
+
+            <Row>
+                <Col xs=12 class="border border-gray-300 rounded-md p-2">
+                    <div class="flex flex-col gap-2">
+                        <For
+                            each=move || encoders.get()
+                            key=|sub_model| sub_model.id
+                            children=move |sub_model| {
+                                view! { <SubModelRow sub_models=encoders id=sub_model.id errors/> }
+                            }
+                        />
+
+                        <div class="flex flex-row gap-2">
+                            <button
+                                on:click=move |_| {
+                                    encoders.update(|models| models.push(SubModel::vision_encoder()))
+                                }
+
+                                class="hover:bg-cyan-600 rounded-md bg-red-400 text-white text-m font-medium pl-2 pr-3 py-2 shadow-sm"
+                            >
+                                "Add Vision Encoder"
+                            </button>
+                            <button
+                                on:click=move |_| {
+                                    encoders.update(|models| models.push(SubModel::projector()))
+                                }
+
+                                class="hover:bg-cyan-600 rounded-md bg-red-400 text-white text-m font-medium pl-2 pr-3 py-2 shadow-sm"
+                            >
+                                "Add Projector"
+                            </button>
+                        </div>
+                    </div>
+                </Col>
+            </Row>
+
+            <Row>
+                <Col xs=12 class="border border-gray-300 rounded-md p-2">
+                    <div class="flex flex-row flex-wrap gap-4">
+                        <FormControl class="flex flex-row">
+                            <Label class="w-28 mr-1">"Checkpoint"</Label>
+                            <Select
+                                options=vec![CheckpointKind::ModelOnly, CheckpointKind::Full]
+
+                                search_text_provider=move |option| format!("{option}")
+                                render_option=move |option| format!("{option}")
+                                selected=ckpt_kind
+                                set_selected=set_ckpt_kind
+                                class="w-48"
+                            />
+                        </FormControl>
+
+                        <FormControl class="flex flex-row">
+                            <Label class="w-28 mr-1">"Layout"</Label>
+                            <Select
+                                options=vec![
+                                    CheckpointLayout::Consolidated,
+                                    CheckpointLayout::Sharded,
+                                ]
+
+                                search_text_provider=move |option| format!("{option}")
+                                render_option=move |option| format!("{option}")
+                                selected=ckpt_layout
+                                set_selected=set_ckpt_layout
+                                class="w-48"
+                            />
+                        </FormControl>
+
+                        <FormControl class="flex flex-row">
+                            <Label class="w-28 mr-1">"Format"</Label>
+                            <Select
+                                options=vec![CheckpointFormat::Safetensors, CheckpointFormat::Torch]
+
+                                search_text_provider=move |option| format!("{option}")
+                                render_option=move |option| format!("{option}")
+                                selected=ckpt_format
+                                set_selected=set_ckpt_format
+                                class="w-48"
+                            />
+                        </FormControl>
+
+                        <FormControl class="flex flex-row">
+                            <Label class="w-28 mr-1">"Bandwidth"</Label>
+                            <NumberInput
+                                min=0.01
+                                max=1000.0
+                                step=0.1
+                                get=bandwidth
+                                set=set_bandwidth
+                                class="w-36"
+                            />
+                            <Label class="ml-1">"GiB/s"</Label>
+                        </FormControl>
+                        <ValidationMessage errors field=Field::Bandwidth/>
                     </div>
                 </Col>
             </Row>
@@ -246,7 +665,14 @@ pub fn CalculatorPage() -> impl IntoView {
                             "Parameters: " {move || params()} " B, Layer Number: "
                             {move || layers() as i64}
                         </P>
-                        <P class="text-gray-500">"Hidden Size: " {move || hidden_size() as i64}</P>
+                        <P class="text-gray-500">
+                            "Hidden Size: " {move || hidden_size() as i64} ", Attention Heads: "
+                            {move || heads() as i64}
+                        </P>
+                        <P class="text-gray-500">
+                            "Sequence Length: " {move || seq_len() as i64} ", Micro Batch: "
+                            {move || micro_batch() as i64}
+                        </P>
                     </div>
                 </Col>
 
@@ -254,17 +680,198 @@ pub fn CalculatorPage() -> impl IntoView {
                     <div class="flex flex-col gap-2">
                         <P class="text-gray-500">"Sequence Parallel: " {sp}</P>
                         <P class="text-gray-500">"Zero Level: " {zero_level}</P>
+                        <P class="text-gray-500">
+                            "TP Size: " {move || tp() as i64} ", DP Size: " {move || dp() as i64}
+                        </P>
                     </div>
                 </Col>
             </Row>
 
-            <Show when=move || { mem_useage() != None } fallback=|| ()>
+            <Show when=move || { mem_useage().is_some() } fallback=|| ()>
                 <Row>
                     <Col xs=6 class="border border-red-300 border-dashed rounded-md p-2">
                         <div class="flex flex-col gap-2">
                             <P class="text-red-400">
                                 "Memory Usage: "
-                                {move || { format!("{:.2} GiB", mem_useage().unwrap_or(0.0)) }}
+                                {move || {
+                                    format!("{:.2} GiB", mem_useage().unwrap_or_default().total_gib())
+                                }}
+
+                            </P>
+                            {move || {
+                                saved_factor()
+                                    .map(|factor| {
+                                        view! {
+                                            <P class="text-red-400">
+                                                {format!(
+                                                    "Calibrated Memory Usage: {:.2} GiB (x{:.3} for {} / {})",
+                                                    mem_useage().unwrap_or_default().total_gib() * factor,
+                                                    factor,
+                                                    device(),
+                                                    framework(),
+                                                )}
+
+                                            </P>
+                                        }
+                                    })
+                            }}
+
+                        </div>
+                    </Col>
+                </Row>
+            </Show>
+
+            <Row>
+                <Col xs=12 class="border border-gray-300 rounded-md p-2">
+                    <div class="flex flex-row flex-wrap gap-4">
+                        <FormControl class="flex flex-row">
+                            <Label class="w-28 mr-1">"Device"</Label>
+                            <TextInput get=device set=set_device class="w-36"/>
+                        </FormControl>
+
+                        <FormControl class="flex flex-row">
+                            <Label class="w-28 mr-1">"Framework"</Label>
+                            <Select
+                                options=vec![
+                                    Framework::Megatron,
+                                    Framework::DeepSpeed,
+                                    Framework::Fsdp,
+                                    Framework::PaddleNLP,
+                                ]
+
+                                search_text_provider=move |option| format!("{option}")
+                                render_option=move |option| format!("{option}")
+                                selected=framework
+                                set_selected=set_framework
+                                class="w-48"
+                            />
+                        </FormControl>
+
+                        <FormControl class="flex flex-row">
+                            <Label class="w-28 mr-1">"Measured"</Label>
+                            <input type="file" accept=".json" on:change=on_measured_change/>
+                        </FormControl>
+                    </div>
+                </Col>
+            </Row>
+
+            <Show when=move || { mem_useage().is_some() && measured().is_some() } fallback=|| ()>
+                <Row>
+                    <Col xs=12 class="border border-red-300 border-dashed rounded-md p-2">
+                        <table class="w-full text-left text-red-400">
+                            <thead>
+                                <tr>
+                                    <th>"Category"</th>
+                                    <th>"Estimated"</th>
+                                    <th>"Measured"</th>
+                                    <th>"Measured / Estimated"</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {move || {
+                                    let estimate = mem_useage().unwrap_or_default();
+                                    let measured = measured().unwrap_or_default();
+                                    MemoryCategory::ALL
+                                        .into_iter()
+                                        .map(|category| {
+                                            let estimated = estimate.get(category);
+                                            let actual = measured.get(category);
+                                            view! {
+                                                <tr>
+                                                    <td>{category.to_string()}</td>
+                                                    <td>{format!("{:.2} GiB", estimated / GIB)}</td>
+                                                    <td>
+                                                        {actual
+                                                            .map(|bytes| format!("{:.2} GiB", bytes / GIB))
+                                                            .unwrap_or_else(|| "-".to_string())}
+                                                    </td>
+                                                    <td>
+                                                        {actual
+                                                            .filter(|_| estimated > 0.0)
+                                                            .map(|bytes| format!("{:.3}", bytes / estimated))
+                                                            .unwrap_or_else(|| "-".to_string())}
+                                                    </td>
+                                                </tr>
+                                            }
+                                        })
+                                        .collect_view()
+                                }}
+                                <tr class="font-semibold">
+                                    <td>"Total"</td>
+                                    <td>
+                                        {move || {
+                                            format!(
+                                                "{:.2} GiB",
+                                                mem_useage().unwrap_or_default().total_gib(),
+                                            )
+                                        }}
+
+                                    </td>
+                                    <td>
+                                        {move || {
+                                            measured()
+                                                .and_then(|m| {
+                                                    let label = match m.is_snapshot() {
+                                                        true => " (in use at snapshot)",
+                                                        false => "",
+                                                    };
+                                                    m.total()
+                                                        .map(|bytes| format!("{:.2} GiB{label}", bytes / GIB))
+                                                })
+                                                .unwrap_or_else(|| "-".to_string())
+                                        }}
+
+                                    </td>
+                                    <td>
+                                        {move || {
+                                            fitted_factor()
+                                                .map(|factor| format!("{factor:.3}"))
+                                                .unwrap_or_else(|| "-".to_string())
+                                        }}
+
+                                    </td>
+                                </tr>
+                            </tbody>
+                        </table>
+                        <div class="flex flex-row-reverse mt-2">
+                            <button
+                                on:click=save_factor
+                                class="hover:bg-cyan-600 rounded-md bg-red-400 text-white text-m font-medium pl-2 pr-3 py-2 shadow-sm"
+                            >
+                                "Save Overhead Factor"
+                            </button>
+                        </div>
+                    </Col>
+                </Row>
+            </Show>
+
+            <Show when=move || { checkpoint().is_some() } fallback=|| ()>
+                <Row>
+                    <Col xs=6 class="border border-red-300 border-dashed rounded-md p-2">
+                        <div class="flex flex-col gap-2">
+                            <P class="text-red-400">
+                                "Checkpoint Size: "
+                                {move || {
+                                    let ckpt = checkpoint().unwrap_or_default();
+                                    format!(
+                                        "{:.2} GiB in {} file(s), {:.2} GiB per rank",
+                                        ckpt.total_gib(),
+                                        ckpt.files,
+                                        ckpt.per_rank_gib(),
+                                    )
+                                }}
+
+                            </P>
+                            <P class="text-red-400">
+                                "Save Time: "
+                                {move || {
+                                    format!("{:.1} s", checkpoint().unwrap_or_default().save_seconds)
+                                }}
+                                ", Load Time: "
+                                {move || {
+                                    format!("{:.1} s", checkpoint().unwrap_or_default().load_seconds)
+                                }}
+
                             </P>
                         </div>
                     </Col>
@@ -272,13 +879,171 @@ pub fn CalculatorPage() -> impl IntoView {
             </Show>
         </Grid>
 
-        <div class="container mx-auto flex flex-row-reverse mt-10">
+        <div class="container mx-auto flex flex-row-reverse gap-2 mt-10">
             <button
                 on:click=calculate
-                class="hover:bg-cyan-600 rounded-md bg-red-400 text-white text-m font-medium pl-2 pr-3 py-2 shadow-sm"
+                disabled=move || !errors.with(Vec::is_empty)
+                class="hover:bg-cyan-600 disabled:opacity-50 disabled:cursor-not-allowed rounded-md bg-red-400 text-white text-m font-medium pl-2 pr-3 py-2 shadow-sm"
             >
                 "Calculate"
             </button>
+            <button
+                on:click=open_print_view
+                disabled=move || !errors.with(Vec::is_empty)
+                class="hover:bg-cyan-600 disabled:opacity-50 disabled:cursor-not-allowed rounded-md bg-red-400 text-white text-m font-medium pl-2 pr-3 py-2 shadow-sm"
+            >
+                "Print View"
+            </button>
+            <DownloadButton
+                file_name="memory-report.json"
+                mime="application/json"
+                content=move |_| Report::new(report_config(), timestamp()).to_json()
+                disabled=Signal::derive(move || !errors.with(Vec::is_empty))
+            >
+                "Export JSON"
+            </DownloadButton>
+            <DownloadButton
+                file_name="memory-report.md"
+                mime="text/markdown"
+                content=move |_| Report::new(report_config(), timestamp()).to_markdown()
+                disabled=Signal::derive(move || !errors.with(Vec::is_empty))
+            >
+                "Export Markdown"
+            </DownloadButton>
+        </div>
+    }
+}
+
+/// Editable row of a sub-model feeding the language model.
+#[component]
+fn SubModelRow(
+    sub_models: RwSignal<Vec<SubModel>>,
+    id: Uuid,
+    errors: Memo<Vec<FieldError>>,
+) -> impl IntoView {
+    let get = move |field: fn(&SubModel) -> f64| {
+        Signal::derive(move || {
+            sub_models
+                .with(|models| models.iter().find(|m| m.id == id).map(field))
+                .unwrap_or_default()
+        })
+    };
+    let set = move |field: fn(&mut SubModel, f64)| {
+        move |value: f64| {
+            sub_models.update(|models| {
+                if let Some(model) = models.iter_mut().find(|m| m.id == id) {
+                    field(model, value);
+                }
+            })
+        }
+    };
+    let update = move |f: &dyn Fn(&mut SubModel)| {
+        sub_models.update(|models| {
+            if let Some(model) = models.iter_mut().find(|m| m.id == id) {
+                f(model);
+            }
+        })
+    };
+    let sub_model = move || sub_models.with(|models| models.iter().find(|m| m.id == id).cloned());
+
+    view! {
+        <div class="flex flex-row flex-wrap gap-2 items-center">
+            <TextInput
+                get=Signal::derive(move || sub_model().map(|m| m.name).unwrap_or_default())
+                set=move |name: String| update(&|model| model.name = name.clone())
+                class="w-36"
+            />
+            <Label>"Params (B)"</Label>
+            <NumberInput
+                min=0.0
+                max=1000.0
+                step=0.001
+                get=get(|m| m.params)
+                set=set(|m, v| m.params = v)
+                class="w-24"
+            />
+            <Label>"Layers"</Label>
+            <NumberInput
+                min=1.0
+                max=1024.0
+                step=1.0
+                get=get(|m| m.layers)
+                set=set(|m, v| m.layers = v)
+                class="w-20"
+            />
+            <Label>"Hidden"</Label>
+            <NumberInput
+                min=1.0
+                max=102400.0
+                step=1.0
+                get=get(|m| m.hidden_size)
+                set=set(|m, v| m.hidden_size = v)
+                class="w-24"
+            />
+            <Label>"Heads"</Label>
+            <NumberInput
+                min=0.0
+                max=1024.0
+                step=1.0
+                get=get(|m| m.heads)
+                set=set(|m, v| m.heads = v)
+                class="w-20"
+            />
+            <Label>"Seq"</Label>
+            <NumberInput
+                min=1.0
+                max=1048576.0
+                step=1.0
+                get=get(|m| m.seq_len)
+                set=set(|m, v| m.seq_len = v)
+                class="w-24"
+            />
+            <Label>"Tokens to LLM"</Label>
+            <NumberInput
+                min=0.0
+                max=1048576.0
+                step=1.0
+                get=get(|m| m.tokens)
+                set=set(|m, v| m.tokens = v)
+                class="w-24"
+            />
+            <Select
+                options=vec![TrainMode::FP16, TrainMode::BF16, TrainMode::FP32]
+
+                search_text_provider=move |option| format!("{:?}", option)
+                render_option=move |option| format!("{:?}", option)
+                selected=Signal::derive(move || {
+                    sub_model().map(|m| m.dtype).unwrap_or(TrainMode::BF16)
+                })
+                set_selected=move |dtype| update(&|model| model.dtype = dtype)
+                class="w-28"
+            />
+            <Checkbox
+                checked=Signal::derive(move || sub_model().is_some_and(|m| m.trainable))
+                set_checked=move |trainable| update(&|model| model.trainable = trainable)
+            />
+            <Label>"Trainable"</Label>
+            <button
+                on:click=move |_| sub_models.update(|models| models.retain(|m| m.id != id))
+                class="hover:bg-cyan-600 rounded-md bg-gray-400 text-white text-sm font-medium px-2 py-1 shadow-sm"
+            >
+                "Remove"
+            </button>
         </div>
+        <ValidationMessage errors field=Field::SubModel(id)/>
+    }
+}
+
+/// Inline messages of the validation errors reported on one field.
+#[component]
+fn ValidationMessage(errors: Memo<Vec<FieldError>>, field: Field) -> impl IntoView {
+    move || {
+        errors.with(|errors| {
+            errors
+                .iter()
+                .filter(|e| e.field == field)
+                .map(|e| view! { <P class="text-sm text-red-500">{e.error.to_string()}</P> })
+                .collect_view()
+        })
     }
 }
@@ -0,0 +1,186 @@
+use super::memory::{MemoryBreakdown, MemoryCategory};
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Peak memory measured by PyTorch, in bytes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeasuredMemory {
+    pub device: Option<String>,
+    pub framework: Option<String>,
+    pub categories: Vec<(MemoryCategory, f64)>,
+    pub peak_allocated: Option<f64>,
+    pub peak_reserved: Option<f64>,
+    /// Memory allocated when a snapshot was taken, which is not a peak.
+    pub snapshot_allocated: Option<f64>,
+}
+
+impl MeasuredMemory {
+    pub fn get(&self, category: MemoryCategory) -> Option<f64> {
+        self.categories
+            .iter()
+            .find(|(c, _)| *c == category)
+            .map(|(_, bytes)| *bytes)
+    }
+
+    /// The measured peak, preferring allocated over reserved memory.
+    ///
+    /// Falls back to the memory allocated at snapshot time when no peak was measured.
+    pub fn total(&self) -> Option<f64> {
+        self.peak().or(self.snapshot_allocated)
+    }
+
+    fn peak(&self) -> Option<f64> {
+        self.peak_allocated
+            .or(self.peak_reserved)
+            .or_else(|| match self.categories.is_empty() {
+                true => None,
+                false => Some(self.categories.iter().map(|(_, bytes)| bytes).sum()),
+            })
+    }
+
+    /// Whether [`MeasuredMemory::total`] is the memory in use at snapshot time rather than a peak.
+    pub fn is_snapshot(&self) -> bool {
+        self.peak().is_none() && self.snapshot_allocated.is_some()
+    }
+}
+
+/// Maps the categories of the PyTorch memory profiler onto the estimator's categories.
+fn category_of(name: &str) -> Option<MemoryCategory> {
+    match name.to_ascii_lowercase().as_str() {
+        "parameter" | "parameters" | "weights" => Some(MemoryCategory::Weights),
+        "gradient" | "gradients" => Some(MemoryCategory::Gradients),
+        "optimizer_state" | "optimizer_states" | "optimizer" => Some(MemoryCategory::Optimizer),
+        "activation" | "activations" | "autograd_detail" | "temporary" | "input" => {
+            Some(MemoryCategory::Activations)
+        }
+        _ => None,
+    }
+}
+
+/// Parses an exported memory summary.
+///
+/// Accepted are the dict returned by `torch.cuda.memory_stats()`, a memory snapshot
+/// (`torch.cuda.memory._snapshot()`) exported as JSON, or an object with per-category
+/// peaks under `categories`. The optional `device` and `framework` keys are kept.
+///
+/// The segments of a snapshot only tell the memory in use when it was taken, so the peak of
+/// its `memory_stats` is preferred when exported alongside.
+pub fn parse_memory_summary(text: &str) -> Result<MeasuredMemory, String> {
+    let json: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let object = json
+        .as_object()
+        .ok_or_else(|| "The memory summary must be a JSON object.".to_string())?;
+
+    let mut measured = MeasuredMemory {
+        device: object
+            .get("device")
+            .and_then(Value::as_str)
+            .map(str::to_owned),
+        framework: object
+            .get("framework")
+            .and_then(Value::as_str)
+            .map(str::to_owned),
+        ..Default::default()
+    };
+
+    let stats = object.get("memory_stats").unwrap_or(&json);
+    measured.peak_allocated = stats.get("allocated_bytes.all.peak").and_then(Value::as_f64);
+    measured.peak_reserved = stats.get("reserved_bytes.all.peak").and_then(Value::as_f64);
+
+    if let Some(segments) = object.get("segments").and_then(Value::as_array) {
+        measured.snapshot_allocated = Some(
+            segments
+                .iter()
+                .filter_map(|segment| segment.get("allocated_size").and_then(Value::as_f64))
+                .sum(),
+        );
+    }
+
+    if let Some(categories) = object.get("categories").and_then(Value::as_object) {
+        for (name, bytes) in categories {
+            let (Some(category), Some(bytes)) = (category_of(name), bytes.as_f64()) else {
+                continue;
+            };
+            match measured.categories.iter_mut().find(|(c, _)| *c == category) {
+                Some((_, total)) => *total += bytes,
+                None => measured.categories.push((category, bytes)),
+            }
+        }
+    }
+
+    match measured.total() {
+        Some(_) => Ok(measured),
+        None => Err("No peak memory found in the summary.".to_string()),
+    }
+}
+
+/// The ratio between the measured and the estimated peak memory.
+pub fn fit_overhead(estimate: &MemoryBreakdown, measured: &MeasuredMemory) -> Option<f64> {
+    let total = estimate.total();
+    match (measured.total(), total > 0.0) {
+        (Some(measured), true) => Some(measured / total),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Calibration {
+    pub device: String,
+    pub framework: String,
+    pub factor: f64,
+}
+
+/// Serializes reading and rewriting the calibrations file, so concurrent saves keep each other.
+#[cfg(feature = "ssr")]
+static CALIBRATIONS_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[cfg(feature = "ssr")]
+fn calibration_file() -> Result<std::path::PathBuf, ServerFnError> {
+    let site_root =
+        std::env::var("LEPTOS_SITE_ROOT").map_err(|e| ServerFnError::new(e.to_string()))?;
+    Ok(std::path::Path::new(&site_root).join("static/calibrations.json"))
+}
+
+#[cfg(feature = "ssr")]
+fn read_calibrations(file_path: &std::path::Path) -> Result<Vec<Calibration>, ServerFnError> {
+    if !file_path.exists() {
+        return Ok(vec![]);
+    }
+    let content =
+        std::fs::read_to_string(file_path).map_err(|e| ServerFnError::new(e.to_string()))?;
+    serde_json::from_str(&content).map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server]
+pub async fn load_calibrations() -> Result<Vec<Calibration>, ServerFnError> {
+    let file_path = calibration_file()?;
+    let _lock = CALIBRATIONS_LOCK
+        .lock()
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    read_calibrations(&file_path)
+}
+
+#[server]
+pub async fn save_calibration(calibration: Calibration) -> Result<(), ServerFnError> {
+    let file_path = calibration_file()?;
+    let _lock = CALIBRATIONS_LOCK
+        .lock()
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let mut calibrations = read_calibrations(&file_path)?;
+    calibrations.retain(|c| c.device != calibration.device || c.framework != calibration.framework);
+    tracing::info!(
+        "Save overhead factor {:.3} for {} / {}.",
+        calibration.factor,
+        calibration.device,
+        calibration.framework
+    );
+    calibrations.push(calibration);
+
+    if let Some(parent) = file_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| ServerFnError::new(e.to_string()))?;
+    }
+    let content =
+        serde_json::to_string_pretty(&calibrations).map_err(|e| ServerFnError::new(e.to_string()))?;
+    std::fs::write(file_path, content).map_err(|e| ServerFnError::new(e.to_string()))
+}
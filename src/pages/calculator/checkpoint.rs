@@ -0,0 +1,195 @@
+use super::model::{optimizer_bytes, tensor_count, weight_bytes, SubModel, GIB};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum CheckpointKind {
+    ModelOnly,
+    Full,
+}
+
+impl std::fmt::Display for CheckpointKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::ModelOnly => "Model Only",
+            Self::Full => "Full (with Optimizer)",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum CheckpointLayout {
+    Consolidated,
+    Sharded,
+}
+
+impl std::fmt::Display for CheckpointLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Consolidated => "Consolidated",
+            Self::Sharded => "Sharded per Rank",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum CheckpointFormat {
+    Safetensors,
+    Torch,
+}
+
+impl CheckpointFormat {
+    /// Approximate header bytes per file and per tensor.
+    fn overhead_bytes(self, tensors: usize) -> f64 {
+        match self {
+            // 8-byte header length followed by a JSON entry per tensor.
+            Self::Safetensors => 8.0 + 128.0 * tensors as f64,
+            // Zip directory plus pickled storage records, each 64-byte aligned.
+            Self::Torch => 1024.0 + 512.0 * tensors as f64,
+        }
+    }
+
+    /// Slowdown relative to raw storage bandwidth when saving.
+    fn save_factor(self) -> f64 {
+        match self {
+            Self::Safetensors => 1.0,
+            Self::Torch => 1.1,
+        }
+    }
+
+    /// Slowdown relative to raw storage bandwidth when loading.
+    /// Safetensors can be memory mapped while torch has to unpickle and copy.
+    fn load_factor(self) -> f64 {
+        match self {
+            Self::Safetensors => 1.0,
+            Self::Torch => 1.25,
+        }
+    }
+}
+
+impl std::fmt::Display for CheckpointFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Safetensors => "safetensors",
+            Self::Torch => "torch (.pt)",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckpointConfig {
+    pub sub_models: Vec<SubModel>,
+    pub zero_level: i32,
+    pub tp: f64,
+    pub dp: f64,
+    pub kind: CheckpointKind,
+    pub layout: CheckpointLayout,
+    pub format: CheckpointFormat,
+    /// Storage bandwidth of a single writer in GiB/s.
+    pub bandwidth: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct CheckpointEstimate {
+    pub total_bytes: f64,
+    pub per_rank_bytes: f64,
+    pub files: usize,
+    pub save_seconds: f64,
+    pub load_seconds: f64,
+}
+
+impl CheckpointEstimate {
+    pub fn total_gib(&self) -> f64 {
+        self.total_bytes / GIB
+    }
+
+    pub fn per_rank_gib(&self) -> f64 {
+        self.per_rank_bytes / GIB
+    }
+}
+
+/// Estimates the on-disk size of a checkpoint and how long it takes to save and load it.
+///
+/// A consolidated checkpoint is written and read by a single rank, so its time is bound by
+/// the whole checkpoint. Sharded checkpoints are written by every rank in parallel and
+/// bound by the largest shard, assuming the storage scales with the number of writers.
+pub fn estimate_checkpoint(config: &CheckpointConfig) -> CheckpointEstimate {
+    let tp = config.tp.max(1.0);
+    let dp = config.dp.max(1.0);
+    let tensors = config
+        .sub_models
+        .iter()
+        .map(|m| tensor_count(m.layers))
+        .sum::<usize>();
+
+    let model = config
+        .sub_models
+        .iter()
+        .map(|m| weight_bytes(m.params, m.dtype))
+        .sum::<f64>();
+    // Frozen sub-models have no optimizer state to save.
+    let trainable = config.sub_models.iter().filter(|m| m.trainable);
+    let (optimizer, optimizer_tensors) = match config.kind {
+        CheckpointKind::ModelOnly => (0.0, 0),
+        CheckpointKind::Full => trainable.fold((0.0, 0), |(bytes, tensors), m| {
+            (
+                bytes + optimizer_bytes(m.params, m.dtype),
+                // Master weights, first and second moments for every tensor.
+                tensors + tensor_count(m.layers) * 3,
+            )
+        }),
+    };
+
+    let (files, total_overhead, per_rank_bytes) = match config.layout {
+        CheckpointLayout::Consolidated => {
+            // Weights and optimizer state go to separate files, both written by one rank.
+            let overhead = config.format.overhead_bytes(tensors)
+                + match config.kind {
+                    CheckpointKind::ModelOnly => 0.0,
+                    CheckpointKind::Full => config.format.overhead_bytes(optimizer_tensors),
+                };
+            let files = match config.kind {
+                CheckpointKind::ModelOnly => 1,
+                CheckpointKind::Full => 2,
+            };
+            (files, overhead, model + optimizer + overhead)
+        }
+        CheckpointLayout::Sharded => {
+            // ZeRO-1 partitions the optimizer state and ZeRO-3 also the weights across data
+            // parallel ranks, without ZeRO every data parallel rank holds the same copy.
+            let zero_shards = |level: i32| match config.zero_level >= level {
+                true => dp,
+                false => 1.0,
+            };
+            let model_shards = tp * zero_shards(3);
+            let optimizer_shards = tp * zero_shards(1);
+            let files = match config.kind {
+                CheckpointKind::ModelOnly => model_shards,
+                CheckpointKind::Full => optimizer_shards.max(model_shards),
+            };
+            // Every shard holds a slice of each tensor.
+            let overhead = config.format.overhead_bytes(tensors + optimizer_tensors);
+            (
+                files as usize,
+                overhead * files,
+                model / model_shards + optimizer / optimizer_shards + overhead,
+            )
+        }
+    };
+    let total_bytes = model + optimizer + total_overhead;
+
+    let seconds = match config.bandwidth > 0.0 {
+        true => per_rank_bytes / (config.bandwidth * GIB),
+        false => f64::INFINITY,
+    };
+
+    CheckpointEstimate {
+        total_bytes,
+        per_rank_bytes,
+        files,
+        save_seconds: seconds * config.format.save_factor(),
+        load_seconds: seconds * config.format.load_factor(),
+    }
+}
@@ -0,0 +1,149 @@
+use super::model::{optimizer_bytes, weight_bytes, SubModel, GIB};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MemoryCategory {
+    Weights,
+    Gradients,
+    Optimizer,
+    Activations,
+}
+
+impl MemoryCategory {
+    pub const ALL: [MemoryCategory; 4] = [
+        Self::Weights,
+        Self::Gradients,
+        Self::Optimizer,
+        Self::Activations,
+    ];
+}
+
+impl std::fmt::Display for MemoryCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Weights => "Weights",
+            Self::Gradients => "Gradients",
+            Self::Optimizer => "Optimizer States",
+            Self::Activations => "Activations",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryConfig {
+    /// Sub-models in forward order, the language model comes last.
+    pub sub_models: Vec<SubModel>,
+    pub micro_batch: f64,
+    pub zero_level: i32,
+    pub sp: bool,
+    pub tp: f64,
+    pub dp: f64,
+}
+
+/// Estimated memory of a single device, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct MemoryBreakdown {
+    pub weights: f64,
+    pub gradients: f64,
+    pub optimizer: f64,
+    pub activations: f64,
+}
+
+impl MemoryBreakdown {
+    pub fn get(&self, category: MemoryCategory) -> f64 {
+        match category {
+            MemoryCategory::Weights => self.weights,
+            MemoryCategory::Gradients => self.gradients,
+            MemoryCategory::Optimizer => self.optimizer,
+            MemoryCategory::Activations => self.activations,
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.weights + self.gradients + self.optimizer + self.activations
+    }
+
+    pub fn total_gib(&self) -> f64 {
+        self.total() / GIB
+    }
+}
+
+impl std::ops::AddAssign for MemoryBreakdown {
+    fn add_assign(&mut self, other: Self) {
+        self.weights += other.weights;
+        self.gradients += other.gradients;
+        self.optimizer += other.optimizer;
+        self.activations += other.activations;
+    }
+}
+
+/// Activations of one sub-model processing `seq_len` tokens.
+///
+/// Transformer layers follow "Reducing Activation Recomputation in Large Transformer Models"
+/// without recomputation. A plain MLP keeps the inputs of its two linear layers and of the
+/// activation function.
+fn activation_bytes(sub_model: &SubModel, seq_len: f64, config: &MemoryConfig) -> f64 {
+    let tp = config.tp.max(1.0);
+    let (s, b, h, a) = (
+        seq_len,
+        config.micro_batch,
+        sub_model.hidden_size,
+        sub_model.heads,
+    );
+    let per_layer = match (a > 0.0, config.sp) {
+        (true, true) => s * b * h * (34.0 / tp + 5.0 * a * s / (h * tp)),
+        (true, false) => s * b * h * (10.0 + 24.0 / tp + 5.0 * a * s / (h * tp)),
+        (false, _) => s * b * h * 3.0 * 2.0 / tp,
+    };
+    // The formulas above assume 16-bit activations.
+    per_layer * sub_model.layers * sub_model.dtype.bytes() / 2.0
+}
+
+/// Estimates the per-device memory of Adam training a composed model.
+///
+/// Weights are sharded by tensor parallelism, and ZeRO shards the optimizer states (level 1),
+/// the gradients (level 2) and the weights (level 3) across data parallel ranks. Frozen
+/// sub-models keep neither gradients nor optimizer states, and only store activations when an
+/// earlier sub-model still needs gradients flowing through them.
+pub fn estimate_memory(config: &MemoryConfig) -> MemoryBreakdown {
+    let tp = config.tp.max(1.0);
+    let dp = config.dp.max(1.0);
+    let zero_shards = |level: i32| match config.zero_level >= level {
+        true => dp,
+        false => 1.0,
+    };
+
+    let extra_tokens: f64 = match config.sub_models.split_last() {
+        Some((_, encoders)) => encoders.iter().map(|m| m.tokens).sum(),
+        None => 0.0,
+    };
+
+    let mut breakdown = MemoryBreakdown::default();
+    let mut needs_grad = false;
+    for (i, sub_model) in config.sub_models.iter().enumerate() {
+        needs_grad |= sub_model.trainable;
+        let seq_len = match i + 1 == config.sub_models.len() {
+            true => sub_model.seq_len + extra_tokens,
+            false => sub_model.seq_len,
+        };
+        let weights = weight_bytes(sub_model.params, sub_model.dtype) / tp;
+
+        breakdown += MemoryBreakdown {
+            weights: weights / zero_shards(3),
+            gradients: match sub_model.trainable {
+                true => weights / zero_shards(2),
+                false => 0.0,
+            },
+            optimizer: match sub_model.trainable {
+                true => optimizer_bytes(sub_model.params, sub_model.dtype) / tp / zero_shards(1),
+                false => 0.0,
+            },
+            activations: match needs_grad {
+                true => activation_bytes(sub_model, seq_len, config),
+                false => 0.0,
+            },
+        };
+    }
+    breakdown
+}
@@ -0,0 +1,82 @@
+use super::TrainMode;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Number of weight tensors in a Llama-style decoder: q/k/v/o projections,
+/// gate/up/down projections and two norms per layer, plus the embedding,
+/// the final norm and the output head.
+pub fn tensor_count(layers: f64) -> usize {
+    layers.max(0.0) as usize * 9 + 3
+}
+
+/// Size of the model weights (`params` is given in billions).
+pub fn weight_bytes(params: f64, train_mode: TrainMode) -> f64 {
+    params * 1e9 * train_mode.bytes()
+}
+
+/// Size of the Adam optimizer state (`params` is given in billions).
+///
+/// Mixed-precision training keeps an FP32 master copy of the weights next to the first and
+/// second moments, while FP32 training only needs the two moments.
+pub fn optimizer_bytes(params: f64, train_mode: TrainMode) -> f64 {
+    let bytes = match train_mode {
+        TrainMode::FP16 | TrainMode::BF16 => 4.0 + 4.0 + 4.0,
+        TrainMode::FP32 => 4.0 + 4.0,
+    };
+    params * 1e9 * bytes
+}
+
+/// One part of a composed model, e.g. the vision encoder, the projector or the language model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubModel {
+    pub id: Uuid,
+    pub name: String,
+    /// Parameters in billions.
+    pub params: f64,
+    pub layers: f64,
+    pub hidden_size: f64,
+    /// Attention heads, zero for plain MLPs such as the projector.
+    pub heads: f64,
+    /// Tokens processed by the sub-model itself.
+    pub seq_len: f64,
+    /// Tokens the sub-model adds to the sequence of the language model, e.g. image tokens.
+    pub tokens: f64,
+    pub dtype: TrainMode,
+    pub trainable: bool,
+}
+
+impl SubModel {
+    /// CLIP ViT-L/14 at 336px, the usual LLaVA vision tower.
+    pub fn vision_encoder() -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: "Vision Encoder".to_string(),
+            params: 0.304,
+            layers: 24.0,
+            hidden_size: 1024.0,
+            heads: 16.0,
+            seq_len: 577.0,
+            tokens: 0.0,
+            dtype: TrainMode::BF16,
+            trainable: false,
+        }
+    }
+
+    /// A two-layer MLP projecting the image features into the language model.
+    pub fn projector() -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: "Projector".to_string(),
+            params: 0.021,
+            layers: 1.0,
+            hidden_size: 4096.0,
+            heads: 0.0,
+            seq_len: 576.0,
+            tokens: 576.0,
+            dtype: TrainMode::BF16,
+            trainable: true,
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use super::memory::MemoryConfig;
+use super::model::SubModel;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Inputs of the calculator form that can be reported on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Field {
+    Params,
+    Layers,
+    HiddenSize,
+    Heads,
+    SeqLen,
+    MicroBatch,
+    Tp,
+    Dp,
+    Bandwidth,
+    /// Any input of the sub-model with the given id.
+    SubModel(Uuid),
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ValidationError {
+    #[error("{0} must be greater than zero")]
+    NotPositive(&'static str),
+    #[error("{0} must be a whole number")]
+    NotInteger(&'static str),
+    #[error("{0} ({1}) must be divisible by {2} ({3})")]
+    NotDivisible(&'static str, i64, &'static str, i64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: Field,
+    pub error: ValidationError,
+}
+
+#[derive(Default)]
+struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    fn push(&mut self, field: Field, error: ValidationError) {
+        self.errors.push(FieldError { field, error });
+    }
+
+    fn positive(&mut self, field: Field, name: &'static str, value: f64) -> bool {
+        let valid = value > 0.0;
+        if !valid {
+            self.push(field, ValidationError::NotPositive(name));
+        }
+        valid
+    }
+
+    /// A whole number of at least one.
+    fn count(&mut self, field: Field, name: &'static str, value: f64) -> bool {
+        if !self.positive(field, name, value) {
+            return false;
+        }
+        let valid = value.fract() == 0.0;
+        if !valid {
+            self.push(field, ValidationError::NotInteger(name));
+        }
+        valid
+    }
+
+    fn divisible(
+        &mut self,
+        field: Field,
+        (name, value): (&'static str, f64),
+        (by_name, by_value): (&'static str, f64),
+    ) {
+        if by_value >= 1.0 && (value as i64) % (by_value as i64) != 0 {
+            self.push(
+                field,
+                ValidationError::NotDivisible(name, value as i64, by_name, by_value as i64),
+            );
+        }
+    }
+
+    /// Checks the inputs of a sub-model, returning whether its sequence length is valid.
+    ///
+    /// Divisibility is only checked for values that are whole numbers themselves.
+    fn sub_model(
+        &mut self,
+        sub_model: &SubModel,
+        field: impl Fn(Field) -> Field,
+        tp: Option<f64>,
+    ) -> bool {
+        self.positive(field(Field::Params), "Parameters", sub_model.params);
+        self.count(field(Field::Layers), "Layer number", sub_model.layers);
+        let seq_len = self.count(field(Field::SeqLen), "Sequence length", sub_model.seq_len);
+        if !self.count(field(Field::HiddenSize), "Hidden size", sub_model.hidden_size) {
+            return seq_len;
+        }
+        // Plain MLPs such as the projector have no attention heads.
+        let has_heads = sub_model.heads != 0.0
+            && self.count(field(Field::Heads), "Attention heads", sub_model.heads);
+        if has_heads {
+            self.divisible(
+                field(Field::HiddenSize),
+                ("Hidden size", sub_model.hidden_size),
+                ("the head count", sub_model.heads),
+            );
+        }
+        if let Some(tp) = tp {
+            self.divisible(
+                field(Field::HiddenSize),
+                ("Hidden size", sub_model.hidden_size),
+                ("the TP size", tp),
+            );
+            if has_heads {
+                self.divisible(
+                    field(Field::Heads),
+                    ("Attention heads", sub_model.heads),
+                    ("the TP size", tp),
+                );
+            }
+        }
+        seq_len
+    }
+}
+
+/// Checks that the configuration is consistent before anything is estimated.
+pub fn validate(config: &MemoryConfig, bandwidth: f64) -> Vec<FieldError> {
+    let mut validator = Validator::default();
+
+    validator.count(Field::MicroBatch, "Micro batch", config.micro_batch);
+    validator.count(Field::Dp, "DP size", config.dp);
+    validator.positive(Field::Bandwidth, "Bandwidth", bandwidth);
+    let tp = match validator.count(Field::Tp, "TP size", config.tp) {
+        true => Some(config.tp),
+        false => None,
+    };
+
+    if let Some((language_model, encoders)) = config.sub_models.split_last() {
+        for encoder in encoders {
+            validator.sub_model(encoder, |_| Field::SubModel(encoder.id), tp);
+        }
+        if language_model.heads == 0.0 {
+            validator.push(Field::Heads, ValidationError::NotPositive("Attention heads"));
+        }
+        let seq_len = validator.sub_model(language_model, |field| field, tp);
+
+        // Sequence parallelism splits the sequence of the language model across TP ranks.
+        let tokens = encoders.iter().map(|m| m.tokens).sum::<f64>();
+        let whole = seq_len && tokens >= 0.0 && tokens.fract() == 0.0;
+        if let (true, true, Some(tp)) = (config.sp, whole, tp) {
+            validator.divisible(
+                Field::SeqLen,
+                ("Sequence length with image tokens", language_model.seq_len + tokens),
+                ("the TP size", tp),
+            );
+        }
+    }
+
+    validator.errors
+}
@@ -1,135 +1,178 @@
+mod store;
+
+use crate::app::AppRoutes;
 use crate::components::*;
-use csv::ReaderBuilder;
+use crate::loss::downsample::downsample;
+use crate::loss::events::{detect_events, EventKind, EventOptions};
+use crate::loss::export::{diffs_to_csv, diffs_to_json, features_to_csv, features_to_json};
+use crate::loss::format::{Format, FormatReader, ParseOptions};
+use crate::loss::run::{find_run, runs_to_loss, Join, Run, SeriesInfo, SeriesKey};
+use crate::loss::scale::{format_tick, Scale};
+use crate::loss::segments::{
+    parse_phases, segment_features, windows, SegmentFeature, Segmentation,
+};
+use crate::loss::smoothing::{smooth, Smoothing};
+use crate::loss::table::{BadRowPolicy, ColumnMapping, Table};
+use crate::loss::tolerance::{verdict, Check, Metric, Rule};
+use crate::loss::{
+    calculate_diff, calculate_features, diff_histogram, row_errors_summary, Feature, HistogramBin,
+    Loss, LossDiff,
+};
 use leptonic::{components::prelude::*, prelude::*};
 use leptos::html::Div;
 use leptos::*;
 use leptos_chartistry::*;
-use leptos_use::{use_drop_zone_with_options, UseDropZoneOptions, UseDropZoneReturn};
-use serde::Deserialize;
+use leptos_router::use_params_map;
+use leptos_use::{
+    use_drop_zone_with_options, use_element_size, UseDropZoneOptions, UseDropZoneReturn,
+    UseElementSizeReturn,
+};
+use std::collections::{HashMap, HashSet};
+pub use store::MAX_RUN_BYTES;
+use store::{load_comparison, save_comparison, save_run, Comparison, RunView, StoredRun};
+use uuid::Uuid;
 use wasm_bindgen::prelude::*;
-use web_sys::{Event, File, FileReader};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::File;
 
-#[derive(Copy, Clone, Debug, Deserialize)]
-struct Loss {
-    step: f64,
-    xpu: f64,
-    gpu: f64,
+/// Parses `#rrggbb`, mixed with white by `fade` between 0 and 1.
+fn hex_colour(hex: &str, fade: f64) -> Option<Colour> {
+    let hex = hex.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| {
+        let value = f64::from(u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()?);
+        Some((value + (255.0 - value) * fade).round() as u8)
+    };
+    Some(Colour::new(channel(0)?, channel(2)?, channel(4)?))
 }
 
-#[derive(Copy, Clone, Debug, Deserialize)]
-struct LossDiff {
-    step: f64,
-    abs: f64,
-    rel: f64,
+/// A corner of the histogram outline, with a count per compared series.
+#[derive(Clone, Debug, PartialEq)]
+struct HistogramPoint {
+    diff: f64,
+    counts: Vec<f64>,
 }
 
-#[derive(Copy, Clone, Debug, Deserialize, Default)]
-pub struct Feature {
-    max_diff: (f64, usize),
-    min_diff: (f64, usize),
-    mean_diff: f64,
-    max_abs_diff: (f64, usize),
-    min_abs_diff: (f64, usize),
-    mean_abs_diff: f64,
-    mean_rel_diff: f64,
-    mean_abs_rel_diff: f64,
-    min_p_diff: (f64, usize),
-    max_n_diff: (f64, usize),
+/// The outline of a histogram, going over the top of every bin.
+fn histogram_outline(histogram: &[HistogramBin]) -> Vec<HistogramPoint> {
+    histogram
+        .iter()
+        .flat_map(|bin| {
+            let counts = bin
+                .counts
+                .iter()
+                .map(|&count| count as f64)
+                .collect::<Vec<_>>();
+            [bin.start, bin.end].map(|diff| HistogramPoint {
+                diff,
+                counts: counts.clone(),
+            })
+        })
+        .collect()
 }
 
-impl Feature {
-    pub fn new(
-        max_diff: (f64, usize),
-        min_diff: (f64, usize),
-        mean_diff: f64,
-        max_abs_diff: (f64, usize),
-        min_abs_diff: (f64, usize),
-        mean_abs_diff: f64,
-        mean_rel_diff: f64,
-        mean_abs_rel_diff: f64,
-        min_p_diff: (f64, usize),
-        max_n_diff: (f64, usize),
-    ) -> Self {
-        Self {
-            max_diff,
-            min_diff,
-            mean_diff,
-            max_abs_diff,
-            min_abs_diff,
-            mean_abs_diff,
-            mean_rel_diff,
-            mean_abs_rel_diff,
-            min_p_diff,
-            max_n_diff,
-        }
-    }
+/// The chart drawn in `container` as a standalone SVG document, with its width and height.
+fn chart_svg(container: &web_sys::Element) -> Option<(String, f64, f64)> {
+    let svg = container.query_selector("svg").ok()??;
+    let bounds = svg.get_bounding_client_rect();
+    let source = svg.outer_html();
+    let source = match source.contains("xmlns=") {
+        true => source,
+        false => source.replacen("<svg", r#"<svg xmlns="http://www.w3.org/2000/svg""#, 1),
+    };
+    Some((source, bounds.width(), bounds.height()))
 }
 
-fn calculate_feature(diffs: &Vec<LossDiff>) -> Feature {
-    let mut max_diff = f64::MIN;
-    let mut max_diff_step: usize = 0;
-    let mut min_diff = f64::MAX;
-    let mut min_diff_step: usize = 0;
-    let mut sum_diff = 0.0;
-    let mut max_abs_diff: f64 = f64::MIN;
-    let mut max_abs_diff_step: usize = 0;
-    let mut min_abs_diff: f64 = f64::MAX;
-    let mut min_abs_diff_step: usize = 0;
-    let mut sum_abs_diff = 0.0;
-    let mut sum_rel_diff = 0.0;
-    let mut sum_abs_rel_diff = 0.0;
-    let mut min_p_diff = f64::MAX;
-    let mut min_p_diff_step: usize = 0;
-    let mut max_n_diff = f64::MIN;
-    let mut max_n_diff_step: usize = 0;
-    let len = diffs.len();
-    for diff in diffs {
-        if max_diff < diff.abs {
-            max_diff = diff.abs;
-            max_diff_step = diff.step as usize;
-        }
-        if min_diff > diff.abs {
-            min_diff = diff.abs;
-            min_diff_step = diff.step as usize;
-        }
-        if max_abs_diff < diff.abs.abs() {
-            max_abs_diff = diff.abs.abs();
-            max_abs_diff_step = diff.step as usize;
-        }
-        if min_abs_diff > diff.abs.abs() {
-            min_abs_diff = diff.abs.abs();
-            min_abs_diff_step = diff.step as usize;
-        }
-        sum_diff += diff.abs;
-        sum_abs_diff += diff.abs.abs();
-        sum_rel_diff += diff.rel;
-        sum_abs_rel_diff += diff.rel.abs();
-        if diff.abs >= 0.0 && min_p_diff > diff.abs {
-            min_p_diff = diff.abs;
-            min_p_diff_step = diff.step as usize;
-        }
-        if diff.abs < 0.0 && max_n_diff < diff.abs {
-            max_n_diff = diff.abs;
-            max_n_diff_step = diff.step as usize;
-        }
+/// Draws an SVG document on a white canvas at twice its size and saves it as PNG.
+fn save_png(file_name: String, source: &str, width: f64, height: f64) -> Result<(), JsValue> {
+    const SCALE: f64 = 2.0;
+    let parts = js_sys::Array::of1(&JsValue::from_str(source));
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_("image/svg+xml");
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+
+    let image = web_sys::HtmlImageElement::new()?;
+    let onload = {
+        let (image, url) = (image.clone(), url.clone());
+        Closure::once_into_js(move || {
+            let draw = || -> Result<(), JsValue> {
+                let canvas = document()
+                    .create_element("canvas")?
+                    .unchecked_into::<web_sys::HtmlCanvasElement>();
+                canvas.set_width((width * SCALE) as u32);
+                canvas.set_height((height * SCALE) as u32);
+                let context = canvas
+                    .get_context("2d")?
+                    .ok_or("no 2d context")?
+                    .unchecked_into::<web_sys::CanvasRenderingContext2d>();
+                context.set_fill_style(&JsValue::from_str("white"));
+                context.fill_rect(0.0, 0.0, width * SCALE, height * SCALE);
+                context.draw_image_with_html_image_element_and_dw_and_dh(
+                    &image,
+                    0.0,
+                    0.0,
+                    width * SCALE,
+                    height * SCALE,
+                )?;
+                download_url(&file_name, &canvas.to_data_url()?)
+            };
+            if let Err(err) = draw() {
+                logging::error!("Failed to save {}: {:?}", file_name, err);
+            }
+            // the image is drawn, the svg is no longer needed
+            let _ = web_sys::Url::revoke_object_url(&url);
+        })
+    };
+    image.set_onload(Some(onload.unchecked_ref()));
+    image.set_src(&url);
+    Ok(())
+}
+
+/// Reads a file a chunk at a time, reporting the fraction read after every chunk, so that the
+/// page stays responsive and shows the progress on long logs.
+async fn read_chunked(file: &File, progress: impl Fn(f64)) -> Result<Vec<u8>, JsValue> {
+    const CHUNK: f64 = 8.0 * 1024.0 * 1024.0;
+    let size = file.size();
+    let mut content = Vec::with_capacity(size as usize);
+    let mut offset = 0.0;
+    while offset < size {
+        let end = (offset + CHUNK).min(size);
+        let chunk = file.slice_with_f64_and_f64(offset, end)?;
+        let buffer = JsFuture::from(chunk.array_buffer()).await?;
+        content.extend(js_sys::Uint8Array::new(&buffer).to_vec());
+        offset = end;
+        progress(offset / size);
     }
+    Ok(content)
+}
 
-    if len > 0 {
-        Feature::new(
-            (max_diff, max_diff_step),
-            (min_diff, min_diff_step),
-            sum_diff / len as f64,
-            (max_abs_diff, max_abs_diff_step),
-            (min_abs_diff, min_abs_diff_step),
-            sum_abs_diff / len as f64,
-            sum_rel_diff / len as f64,
-            sum_abs_rel_diff / len as f64,
-            (min_p_diff, min_p_diff_step),
-            (max_n_diff, max_n_diff_step),
-        )
-    } else {
-        Feature::default()
+/// Parses a file a slice at a time, reporting the fraction parsed after every slice, so that
+/// the page stays responsive and shows the progress on long logs.
+async fn parse_sliced(
+    format: Format,
+    file_name: &str,
+    content: &[u8],
+    options: &ParseOptions,
+    progress: impl Fn(f64),
+) -> Result<(Table, Format), String> {
+    let text = String::from_utf8_lossy(content);
+    let (mut reader, format) = format.reader(file_name, content, &text, options)?;
+    while !reader.read(FormatReader::SLICE)? {
+        progress(reader.progress());
+        next_tick().await;
     }
+    Ok((reader.finish()?, format))
+}
+
+/// Resolves on the next turn of the event loop, after the page has been rendered.
+async fn next_tick() {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        let _ = window().set_timeout_with_callback(&resolve);
+    });
+    let _ = JsFuture::from(promise).await;
 }
 
 #[component]
@@ -140,44 +183,264 @@ pub fn DrawPage() -> impl IntoView {
     let (diff, set_diff) = create_signal(vec![]);
     let (global_loss, set_global_loss) = create_signal(vec![]);
     let (global_diff, set_global_diff) = create_signal(vec![]);
-    let (global_len, set_global_len) = create_signal(0.0);
+    // first and last step of all runs, the range is filtered by step
+    let (first_step, set_first_step) = create_signal(0.0);
+    let (last_step, set_last_step) = create_signal(0.0);
     let (start, set_start) = create_signal(0.0);
     let (end, set_end) = create_signal(-1.0);
-    let (feature, set_feature) = create_signal(Feature::default());
+    let runs = create_rw_signal(Vec::<Run>::new());
+    // the plotted series of all visible runs
+    let (series, set_series) = create_signal(Vec::<SeriesInfo>::new());
+    let reference = create_rw_signal(Option::<SeriesKey>::None);
+    // compare the reference against one series, or against all of them
+    let compare = create_rw_signal(Option::<SeriesKey>::None);
+    let (bad_row_policy, set_bad_row_policy) = create_signal(BadRowPolicy::default());
+    let (join, set_join) = create_signal(Join::default());
+    let (format, set_format) = create_signal(Format::default());
+    let (pattern, set_pattern) =
+        create_signal(r"step (?P<step>\d+).*loss (?P<loss>\S+)".to_string());
+    let (step_key, set_step_key) = create_signal(String::new());
+    let (metric_keys, set_metric_keys) = create_signal(String::new());
+    // the rows of all runs aligned by step, before smoothing
+    let (aligned, set_aligned) = create_signal(Vec::<Loss>::new());
+    let (smoothing, set_smoothing) = create_signal(Smoothing::default());
+    let (weight, set_weight) = create_signal(0.6);
+    let (window, set_window) = create_signal(10.0);
+    let (stats_on_smoothed, set_stats_on_smoothed) = create_signal(false);
+    let rules = create_rw_signal(Rule::defaults());
+    let defaults = EventOptions::default();
+    let (spike_window, set_spike_window) = create_signal(defaults.window as f64);
+    let (spike_z_score, set_spike_z_score) = create_signal(defaults.z_score);
+    let (divergence, set_divergence) = create_signal(defaults.divergence);
+    let (divergence_steps, set_divergence_steps) = create_signal(defaults.divergence_steps as f64);
+    let (segmentation, set_segmentation) = create_signal(Segmentation::default());
+    let (segment_size, set_segment_size) = create_signal(1000.0);
+    let (phases, set_phases) = create_signal(String::new());
+    let (loss_scale, set_loss_scale) = create_signal(Scale::Linear);
+    let (diff_scale, set_diff_scale) = create_signal(Scale::Linear);
+    let (linear_width, set_linear_width) = create_signal(0.001);
+    let (rel_percent, set_rel_percent) = create_signal(false);
+    let (loss_y_min, set_loss_y_min) = create_signal(String::new());
+    let (loss_y_max, set_loss_y_max) = create_signal(String::new());
+    let (diff_y_min, set_diff_y_min) = create_signal(String::new());
+    let (diff_y_max, set_diff_y_max) = create_signal(String::new());
+    let toasts = expect_context::<Toasts>();
 
-    let loss_series = Series::new(|loss: &Loss| loss.step)
-        .line(
-            Line::new(|loss: &Loss| loss.xpu)
-                .with_name("XPU")
-                .with_interpolation(Interpolation::Linear),
-        )
-        .line(
-            Line::new(|loss: &Loss| loss.gpu)
-                .with_name("GPU")
-                .with_interpolation(Interpolation::Linear),
+    let series_name = move |key: Option<SeriesKey>| match key {
+        None => "All".to_string(),
+        Some(key) => series.with(|series| {
+            series
+                .iter()
+                .find(|info| info.key == key)
+                .map(|info| info.name.clone())
+                .unwrap_or_default()
+        }),
+    };
+
+    // the diff series shown, each with its position in `LossDiff`
+    let compared = move || {
+        let (reference, compare) = (reference(), compare());
+        series()
+            .into_iter()
+            .filter(|info| Some(info.key) != reference)
+            .enumerate()
+            .filter(|(_, info)| compare.is_none_or(|key| key == info.key))
+            .collect::<Vec<_>>()
+    };
+    let features = create_memo(move |_| diff.with(|diff| calculate_features(diff, &compared())));
+    let verdicts = create_memo(move |_| {
+        rules.with(|rules| {
+            diff.with(|diff| {
+                compared()
+                    .into_iter()
+                    .map(|(i, info)| (info.name, verdict(rules, diff, i)))
+                    .collect::<Vec<_>>()
+            })
+        })
+    });
+    let segments = create_memo(move |_| match segmentation() {
+        Segmentation::Windows => Ok(diff.with(|diff| windows(diff, segment_size()))),
+        Segmentation::Phases => parse_phases(&phases()),
+    });
+    let segment_tables = create_memo(move |_| {
+        let segments = segments().unwrap_or_default();
+        rules.with(|rules| {
+            diff.with(|diff| {
+                compared()
+                    .into_iter()
+                    .map(|(i, info)| (info.name, segment_features(diff, i, &segments, rules)))
+                    .collect::<Vec<_>>()
+            })
+        })
+    });
+    let passed = move || {
+        verdicts.with(|verdicts| {
+            !verdicts.is_empty()
+                && verdicts
+                    .iter()
+                    .all(|(_, checks)| checks.iter().all(|(_, check)| check.passed))
+        })
+    };
+
+    // events of the whole runs, whatever the range
+    let events = create_memo(move |_| {
+        let options = EventOptions {
+            window: spike_window() as usize,
+            z_score: spike_z_score(),
+            divergence: divergence(),
+            divergence_steps: divergence_steps() as usize,
+        };
+        global_loss.with(|loss| {
+            global_diff.with(|diffs| detect_events(&series(), loss, diffs, &compared(), &options))
+        })
+    });
+    // marker positions by step, drawn as a line of markers that only has values at events
+    let markers = move |kinds: &[EventKind]| {
+        let mut markers = HashMap::new();
+        events.with(|events| {
+            for event in events.iter().filter(|event| kinds.contains(&event.kind)) {
+                markers.entry(event.step.to_bits()).or_insert(event.value);
+            }
+        });
+        markers
+    };
+
+    // manual y limit in data units, none when left empty
+    let y_limit = |limit: String, scale: Scale, width: f64| {
+        limit
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .map(|limit| scale.apply(limit, width))
+            .filter(|limit| limit.is_finite())
+    };
+
+    // with smoothing on, the raw curves are drawn faint behind the smoothed ones
+    let loss_series = move || {
+        let smoothed = smoothing() != Smoothing::None;
+        let markers = markers(&[EventKind::Spike, EventKind::NonFinite]);
+        let (scale, width) = (loss_scale(), linear_width());
+        let y = move |value: Option<f64>| value.map_or(f64::NAN, |value| scale.apply(value, width));
+        let series = series().into_iter().enumerate().fold(
+            Series::new(|loss: &Loss| loss.step),
+            |series, (i, info)| {
+                let raw = Line::new(move |loss: &Loss| y(loss.values.get(i).copied().flatten()))
+                    .with_interpolation(Interpolation::Linear);
+                match smoothed {
+                    true => series
+                        .line(
+                            raw.with_name(format!("{} Raw", info.name))
+                                .with_colour(hex_colour(&info.colour, 0.7)),
+                        )
+                        .line(
+                            Line::new(move |loss: &Loss| {
+                                y(loss.smoothed.get(i).copied().flatten())
+                            })
+                            .with_name(info.name)
+                            .with_colour(hex_colour(&info.colour, 0.0))
+                            .with_interpolation(Interpolation::Linear),
+                        ),
+                    false => series.line(
+                        raw.with_name(info.name)
+                            .with_colour(hex_colour(&info.colour, 0.0)),
+                    ),
+                }
+            },
         );
+        series
+            .line(
+                Line::new(move |loss: &Loss| y(markers.get(&loss.step.to_bits()).copied()))
+                    .with_name("Spikes & NaN/Inf")
+                    .with_colour(hex_colour("#dc2626", 0.0))
+                    .with_marker(MarkerShape::Circle),
+            )
+            .with_y_range(
+                y_limit(loss_y_min(), scale, width),
+                y_limit(loss_y_max(), scale, width),
+            )
+    };
 
-    // draw loss diff curve
-    let diff_series = Series::new(|diff: &LossDiff| diff.step)
-        .line(
-            Line::new(|diff: &LossDiff| diff.abs)
-                .with_name("Abs")
-                .with_interpolation(Interpolation::Linear),
-        )
-        .line(
-            Line::new(|diff: &LossDiff| diff.rel)
-                .with_name("Rel")
-                .with_interpolation(Interpolation::Linear),
+    // draw loss diff curve, one abs and one rel line per compared series
+    let diff_series = move || {
+        let markers = markers(&[EventKind::Divergence]);
+        let (scale, width) = (diff_scale(), linear_width());
+        let y = move |value: Option<f64>| value.map_or(f64::NAN, |value| scale.apply(value, width));
+        let (percent, rel_name) = match rel_percent() {
+            true => (100.0, "Rel (%)"),
+            false => (1.0, "Rel"),
+        };
+        let series = compared().into_iter().fold(
+            Series::new(|diff: &LossDiff| diff.step),
+            |series, (i, info)| {
+                series
+                    .line(
+                        Line::new(move |diff: &LossDiff| y(diff.abs.get(i).copied().flatten()))
+                            .with_name(format!("{} Abs", info.name))
+                            .with_colour(hex_colour(&info.colour, 0.0))
+                            .with_interpolation(Interpolation::Linear),
+                    )
+                    .line(
+                        Line::new(move |diff: &LossDiff| {
+                            y(diff.rel.get(i).copied().flatten().map(|rel| rel * percent))
+                        })
+                        .with_name(format!("{} {rel_name}", info.name))
+                        .with_interpolation(Interpolation::Linear),
+                    )
+            },
         );
+        series
+            .line(
+                Line::new(move |diff: &LossDiff| {
+                    y(markers.get(&diff.step.to_bits()).map(|rel| rel * percent))
+                })
+                .with_name("Divergence")
+                .with_colour(hex_colour("#dc2626", 0.0))
+                .with_marker(MarkerShape::Circle),
+            )
+            .with_y_range(
+                y_limit(diff_y_min(), scale, width),
+                y_limit(diff_y_max(), scale, width),
+            )
+    };
+
+    // distribution of the diffs in range, one outline per compared series
+    let histogram = create_memo(move |_| {
+        let compared = compared();
+        let series = compared.iter().map(|(i, _)| *i).collect::<Vec<_>>();
+        diff.with(|diff| histogram_outline(&diff_histogram(diff, &series, 30)))
+    });
+    let histogram_series = move || {
+        compared().into_iter().enumerate().fold(
+            Series::new(|point: &HistogramPoint| point.diff),
+            |series, (i, (_, info))| {
+                series.line(
+                    Line::new(move |point: &HistogramPoint| {
+                        point.counts.get(i).copied().unwrap_or(f64::NAN)
+                    })
+                    .with_name(info.name)
+                    .with_colour(hex_colour(&info.colour, 0.0))
+                    .with_interpolation(Interpolation::Linear),
+                )
+            },
+        )
+    };
 
     // set axis ticks
     let x_ticks = TickLabels::default();
     let y_ticks = TickLabels::aligned_floats();
+    // the ticks of a scaled axis are labelled with the values rather than their positions
+    let scaled_ticks = move |scale: Scale, width: f64| match scale {
+        Scale::Linear => TickLabels::aligned_floats(),
+        scale => TickLabels::aligned_floats()
+            .with_format(move |position: &f64, _| format_tick(scale.invert(*position, width))),
+    };
 
     // drag and drop files
     let (drag_color, set_drag_color) = create_signal("#e66956");
     let (chart_visibility, set_chart_visibility) = create_signal("none");
     let drop_zone_el = create_node_ref::<Div>();
+    // files being read or parsed, with the fraction done so far
+    let uploads = create_rw_signal(Vec::<(Uuid, String, &'static str, f64)>::new());
     let update_data = create_action(move |file: &File| {
         let file = file.to_owned();
         set_file_name(format!("[ {} ]", file.name()));
@@ -186,44 +449,56 @@ pub fn DrawPage() -> impl IntoView {
         logging::log!("File type: {}", file.type_());
         logging::log!("File last modified time: {}", file.last_modified());
         async move {
-            let file_reader = FileReader::new().unwrap();
-            let onloadend = Closure::wrap(Box::new(move |event: Event| {
-                let file_reader = event.target().unwrap().dyn_into::<FileReader>().unwrap();
-                let content = file_reader.result().unwrap();
-                let raw_data = js_sys::Uint8Array::from(content).to_string();
-                let data = format!("step,xpu,gpu\n{}", raw_data);
-                let mut csv_reader = ReaderBuilder::new().from_reader(data.as_bytes());
-                let mut loss = vec![];
-                let mut diff = vec![];
-                for result in csv_reader.deserialize() {
-                    let record: Loss = result.unwrap();
-                    let rel_diff = (record.xpu - record.gpu) / record.gpu;
-                    diff.push(LossDiff {
-                        step: record.step,
-                        abs: record.xpu - record.gpu,
-                        rel: match rel_diff.is_nan() {
-                            true => (record.xpu - record.gpu) / (record.gpu + 1e-8),
-                            false => rel_diff,
-                        },
-                    });
-                    loss.push(record);
+            let file_name = file.name();
+            let id = Uuid::new_v4();
+            uploads.update(|uploads| uploads.push((id, file_name.clone(), "Reading", 0.0)));
+            let progress = move |phase: &'static str| {
+                move |done: f64| {
+                    uploads.update(|uploads| {
+                        if let Some(upload) = uploads.iter_mut().find(|upload| upload.0 == id) {
+                            upload.2 = phase;
+                            upload.3 = done;
+                        }
+                    })
                 }
-                let data_len = loss.len();
-                set_feature(calculate_feature(&diff));
-                set_start(0.0);
-                set_end(data_len as f64);
-                set_loss(loss.clone());
-                set_diff(diff.clone());
-                set_global_loss(loss);
-                set_global_diff(diff);
-                set_global_len(data_len as f64);
-                set_chart_visibility("block");
-            }) as Box<dyn FnMut(_)>);
-
-            file_reader.set_onloadend(Some(onloadend.as_ref().unchecked_ref()));
-            file_reader.read_as_text(&file).unwrap();
-            // prevent the callback from being dropped
-            onloadend.forget();
+            };
+            let parsed = match read_chunked(&file, progress("Reading")).await {
+                Ok(content) => {
+                    let options = ParseOptions {
+                        pattern: pattern.get_untracked(),
+                        step_key: step_key.get_untracked(),
+                        metric_keys: metric_keys.get_untracked(),
+                    };
+                    progress("Parsing")(0.0);
+                    let format = format.get_untracked();
+                    let parsing = progress("Parsing");
+                    Ok(parse_sliced(format, &file_name, &content, &options, parsing).await)
+                }
+                Err(err) => Err(err),
+            };
+            uploads.update(|uploads| uploads.retain(|upload| upload.0 != id));
+
+            let (header, body) = match parsed {
+                Ok(Ok((table, format))) => {
+                    logging::log!("Read {} as {}", file_name, format);
+                    runs.update(|runs| runs.push(Run::new(&file_name, table, runs.len())));
+                    set_chart_visibility("block");
+                    return;
+                }
+                Ok(Err(err)) => ("Parse Failed!", format!("Cannot parse {file_name}: {err}")),
+                Err(err) => {
+                    logging::error!("Failed to read {}: {:?}", file_name, err);
+                    ("Read Failed!", format!("Cannot read {file_name}."))
+                }
+            };
+            toasts.push(Toast {
+                id: Uuid::new_v4(),
+                created_at: time::OffsetDateTime::now_utc(),
+                variant: ToastVariant::Error,
+                header: header.into_view(),
+                body: body.into_view(),
+                timeout: ToastTimeout::CustomDelay(time::Duration::seconds(5)),
+            });
         }
     });
     let UseDropZoneReturn {
@@ -243,19 +518,376 @@ pub fn DrawPage() -> impl IntoView {
         false => set_drag_color("#e66956"),
     });
 
-    let replot = move |_| {
-        let start = usize::max(start.get_untracked() as usize, 0);
-        let mut end = usize::min(
-            end.get_untracked() as usize,
-            global_len.get_untracked() as usize,
-        );
-        end = end.max(start);
-        let current_loss = global_loss.get_untracked()[start..end].to_owned();
-        let current_diff = global_diff.get_untracked()[start..end].to_owned();
-        set_feature(calculate_feature(&current_diff));
+    // Re-align all runs whenever one of them or their column mapping changes.
+    create_effect(
+        move |previous: Option<(Vec<(Uuid, ColumnMapping, BadRowPolicy)>, Option<(f64, f64)>)>| {
+            let policy = bad_row_policy();
+            let (infos, loss, errors) = runs.with(|runs| runs_to_loss(runs, policy, join()));
+
+            // report bad rows once per run, mapping and policy rather than on every re-plot
+            let (reported, bounds) = previous.unwrap_or_default();
+            let parsed = runs.with(|runs| {
+                runs.iter()
+                    .map(|run| (run.id, run.mapping.clone(), policy))
+                    .collect::<Vec<_>>()
+            });
+            for (id, errors) in errors {
+                let Some(parse) = parsed.iter().find(|parse| parse.0 == id) else {
+                    continue;
+                };
+                if errors.is_empty() || reported.contains(parse) {
+                    continue;
+                }
+                let name = runs.with(|runs| {
+                    runs.iter()
+                        .find(|run| run.id == id)
+                        .map(|run| run.name.clone())
+                        .unwrap_or_default()
+                });
+                toasts.push(Toast {
+                    id: Uuid::new_v4(),
+                    created_at: time::OffsetDateTime::now_utc(),
+                    variant: match policy {
+                        BadRowPolicy::Reject => ToastVariant::Error,
+                        _ => ToastVariant::Warn,
+                    },
+                    header: format!("Bad Rows in {name}!").into_view(),
+                    body: row_errors_summary(&errors, policy).into_view(),
+                    timeout: ToastTimeout::CustomDelay(time::Duration::seconds(10)),
+                });
+            }
+
+            // keep the reference and the compared series among the plotted series
+            if reference
+                .with_untracked(|reference| infos.iter().all(|info| Some(info.key) != *reference))
+            {
+                let key = runs.with(|runs| {
+                    runs.iter()
+                        .filter(|run| run.visible)
+                        .find_map(Run::reference)
+                });
+                reference.set(key);
+            }
+            if compare.with_untracked(|compare| {
+                compare.is_some_and(|key| infos.iter().all(|info| info.key != key))
+            }) {
+                compare.set(None);
+            }
+
+            // only touch the chart series when they change, as that rebuilds the charts
+            if series.get_untracked() != infos {
+                set_series(infos);
+            }
+            let (first, last) = match (loss.first(), loss.last()) {
+                (Some(first), Some(last)) => (first.step, last.step),
+                _ => (0.0, 0.0),
+            };
+            // renaming, recolouring or hiding a run keeps the zoom, only new bounds reset it
+            if bounds != Some((first, last)) {
+                set_first_step(first);
+                set_last_step(last);
+                set_start(first);
+                set_end(last);
+            }
+            set_aligned(loss);
+            (parsed, Some((first, last)))
+        },
+    );
+
+    // only plot the rows between start and end
+    let replot_range = move || {
+        let (start, end) = (start.get_untracked(), end.get_untracked());
+        let in_range = |step: f64| start <= step && step <= end;
+        let current_loss = global_loss.with_untracked(|loss: &Vec<Loss>| {
+            loss.iter()
+                .filter(|row| in_range(row.step))
+                .cloned()
+                .collect::<Vec<_>>()
+        });
+        let current_diff = global_diff.with_untracked(|diff: &Vec<LossDiff>| {
+            diff.iter()
+                .filter(|row| in_range(row.step))
+                .cloned()
+                .collect::<Vec<_>>()
+        });
         set_loss(current_loss);
         set_diff(current_diff);
     };
+    let replot = move |_| replot_range();
+
+    let zoom = move |(from, to): (f64, f64)| {
+        set_start(from);
+        set_end(to);
+        replot_range();
+    };
+    let reset_zoom = move || zoom((first_step.get_untracked(), last_step.get_untracked()));
+
+    // zoom the range in on an event
+    let zoom_to = move |step: f64| {
+        let (first, last) = (first_step.get_untracked(), last_step.get_untracked());
+        let margin = ((last - first) / 20.0).max(10.0);
+        zoom(((step - margin).max(first), (step + margin).min(last)));
+    };
+
+    // the page as stored for a shareable link
+    let comparison = move || Comparison {
+        runs: runs.with_untracked(|runs| runs.iter().map(RunView::new).collect()),
+        reference: reference.get_untracked(),
+        compare: compare.get_untracked(),
+        bad_row_policy: bad_row_policy.get_untracked(),
+        join: join.get_untracked(),
+        start: start.get_untracked(),
+        end: end.get_untracked(),
+        smoothing: smoothing.get_untracked(),
+        weight: weight.get_untracked(),
+        window: window.get_untracked(),
+        stats_on_smoothed: stats_on_smoothed.get_untracked(),
+        rules: rules.get_untracked(),
+        spike_window: spike_window.get_untracked(),
+        spike_z_score: spike_z_score.get_untracked(),
+        divergence: divergence.get_untracked(),
+        divergence_steps: divergence_steps.get_untracked(),
+        segmentation: segmentation.get_untracked(),
+        segment_size: segment_size.get_untracked(),
+        phases: phases.get_untracked(),
+        loss_scale: loss_scale.get_untracked(),
+        diff_scale: diff_scale.get_untracked(),
+        linear_width: linear_width.get_untracked(),
+        rel_percent: rel_percent.get_untracked(),
+        loss_y_limits: (loss_y_min.get_untracked(), loss_y_max.get_untracked()),
+        diff_y_limits: (diff_y_min.get_untracked(), diff_y_max.get_untracked()),
+    };
+    // the ids runs already stored on the server are stored under, they never change after upload
+    let stored_runs = create_rw_signal(HashMap::<Uuid, Uuid>::new());
+    let (share_link, set_share_link) = create_signal(Option::<String>::None);
+    let restore = move |comparison: Comparison, stored: Vec<StoredRun>| {
+        set_bad_row_policy(comparison.bad_row_policy);
+        set_join(comparison.join);
+        set_smoothing(comparison.smoothing);
+        set_weight(comparison.weight);
+        set_window(comparison.window);
+        set_stats_on_smoothed(comparison.stats_on_smoothed);
+        rules.set(comparison.rules);
+        set_spike_window(comparison.spike_window);
+        set_spike_z_score(comparison.spike_z_score);
+        set_divergence(comparison.divergence);
+        set_divergence_steps(comparison.divergence_steps);
+        set_segmentation(comparison.segmentation);
+        set_segment_size(comparison.segment_size);
+        set_phases(comparison.phases);
+        set_loss_scale(comparison.loss_scale);
+        set_diff_scale(comparison.diff_scale);
+        set_linear_width(comparison.linear_width);
+        set_rel_percent(comparison.rel_percent);
+        set_loss_y_min(comparison.loss_y_limits.0);
+        set_loss_y_max(comparison.loss_y_limits.1);
+        set_diff_y_min(comparison.diff_y_limits.0);
+        set_diff_y_max(comparison.diff_y_limits.1);
+
+        stored_runs.set(stored.iter().map(|run| (run.id, run.id)).collect());
+        let mut tables = stored
+            .into_iter()
+            .map(|run| (run.id, run.table))
+            .collect::<HashMap<_, _>>();
+        runs.set(
+            comparison
+                .runs
+                .into_iter()
+                .filter_map(|view| {
+                    let table = tables.remove(&view.id)?;
+                    Some(view.into_run(table))
+                })
+                .collect(),
+        );
+        set_chart_visibility("block");
+        // re-aligning the runs resets the series and the range, so they come last
+        let loaded = |key: Option<SeriesKey>| {
+            key.filter(|key| {
+                runs.with_untracked(|runs| {
+                    find_run(runs, key.run)
+                        .is_some_and(|run| run.visible && run.mapping.series.contains(&key.column))
+                })
+            })
+        };
+        // keys of links shared before the runs got their stored ids match no run
+        let fallback = || {
+            runs.with_untracked(|runs| {
+                runs.iter()
+                    .filter(|run| run.visible)
+                    .find_map(Run::reference)
+            })
+        };
+        reference.set(loaded(comparison.reference).or_else(fallback));
+        compare.set(loaded(comparison.compare));
+        zoom((comparison.start, comparison.end));
+    };
+
+    // open the comparison of a shared link
+    let params = use_params_map();
+    let comparison_id = move || {
+        params.with(|params| {
+            params
+                .get("comparison_id")
+                .and_then(|id| Uuid::parse_str(id).ok())
+        })
+    };
+    let loaded = create_local_resource(comparison_id, |id| async move {
+        match id {
+            Some(id) => Some(load_comparison(id).await),
+            None => None,
+        }
+    });
+    create_effect(move |_| match loaded.get().flatten() {
+        Some(Ok((comparison, stored))) => restore(comparison, stored),
+        Some(Err(err)) => toasts.push(Toast {
+            id: Uuid::new_v4(),
+            created_at: time::OffsetDateTime::now_utc(),
+            variant: ToastVariant::Error,
+            header: "Load Failed!".to_owned().into_view(),
+            body: format!("Cannot open the comparison: {err}").into_view(),
+            timeout: ToastTimeout::CustomDelay(time::Duration::seconds(5)),
+        }),
+        None => {}
+    });
+
+    // store the runs not stored yet and the comparison, for a link that restores the page
+    let share = create_action(move |_: &()| {
+        let mut comparison = comparison();
+        let unstored = runs.with_untracked(|runs| {
+            stored_runs.with_untracked(|stored| {
+                runs.iter()
+                    .filter(|run| !stored.contains_key(&run.id))
+                    .map(StoredRun::new)
+                    .collect::<Vec<_>>()
+            })
+        });
+        async move {
+            for run in unstored {
+                let id = run.id;
+                let stored_id = save_run(run).await?;
+                stored_runs.update(|stored| {
+                    stored.insert(id, stored_id);
+                });
+            }
+            stored_runs.with_untracked(|stored| {
+                for view in &mut comparison.runs {
+                    if let Some(id) = stored.get(&view.id) {
+                        view.id = *id;
+                    }
+                }
+                let keys = [&mut comparison.reference, &mut comparison.compare];
+                for key in keys.into_iter().flatten() {
+                    if let Some(id) = stored.get(&key.run) {
+                        key.run = *id;
+                    }
+                }
+            });
+            save_comparison(comparison).await
+        }
+    });
+    create_effect(move |_| match share.value().get() {
+        Some(Ok(id)) => {
+            let path = format!("{}/{id}", AppRoutes::Draw);
+            // point the address bar at the stored comparison without reloading the page
+            if let Ok(history) = leptos::window().history() {
+                let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&path));
+            }
+            let origin = leptos::window().location().origin().unwrap_or_default();
+            set_share_link(Some(format!("{origin}{path}")));
+        }
+        Some(Err(err)) => toasts.push(Toast {
+            id: Uuid::new_v4(),
+            created_at: time::OffsetDateTime::now_utc(),
+            variant: ToastVariant::Error,
+            header: "Share Failed!".to_owned().into_view(),
+            body: err.to_string().into_view(),
+            timeout: ToastTimeout::CustomDelay(time::Duration::seconds(5)),
+        }),
+        None => {}
+    });
+
+    // the charts share the hovered step and the brushed range
+    let cursor = ChartCursor {
+        hover: create_rw_signal(None),
+        brush: create_rw_signal(None),
+    };
+    let loss_domain = Signal::derive(move || {
+        loss.with(|loss| match (loss.first(), loss.last()) {
+            (Some(first), Some(last)) => (first.step, last.step),
+            _ => (0.0, 0.0),
+        })
+    });
+    let loss_chart = create_node_ref::<Div>();
+    let diff_chart = create_node_ref::<Div>();
+
+    // the charts draw about a point per pixel of every line, the statistics use all rows
+    let UseElementSizeReturn {
+        width: chart_width, ..
+    } = use_element_size(loss_chart);
+    let points = move || (chart_width.get() as usize).max(200);
+    let event_steps = create_memo(move |_| {
+        events.with(|events| {
+            events
+                .iter()
+                .map(|event| event.step.to_bits())
+                .collect::<HashSet<_>>()
+        })
+    });
+    let plotted_loss = create_memo(move |_| {
+        event_steps.with(|steps| {
+            loss.with(|loss| downsample(loss, points(), |row| steps.contains(&row.step.to_bits())))
+        })
+    });
+    let plotted_diff = create_memo(move |_| {
+        event_steps.with(|steps| {
+            diff.with(|diff| downsample(diff, points(), |row| steps.contains(&row.step.to_bits())))
+        })
+    });
+    // exports are of what is drawn: the plotted range of the visible series
+    let export_name = move |name: &str, extension: &str| {
+        let (first, last) = loss_domain.get();
+        format!("{name}_{first}-{last}.{extension}")
+    };
+    let save_chart = move |chart: NodeRef<Div>, name: &'static str, extension: &'static str| {
+        let file_name = export_name(name, extension);
+        let Some((source, width, height)) =
+            chart.get_untracked().and_then(|chart| chart_svg(&chart))
+        else {
+            return;
+        };
+        let saved = match extension {
+            "png" => save_png(file_name.clone(), &source, width, height),
+            _ => download(&file_name, "image/svg+xml", &source),
+        };
+        if let Err(err) = saved {
+            logging::error!("Failed to save {}: {:?}", file_name, err);
+        }
+    };
+    let diff_domain = Signal::derive(move || {
+        diff.with(|diff| match (diff.first(), diff.last()) {
+            (Some(first), Some(last)) => (first.step, last.step),
+            _ => (0.0, 0.0),
+        })
+    });
+
+    // Smooth and diff the aligned rows whenever they, the smoothing or the reference change,
+    // keeping the current range.
+    create_effect(move |_| {
+        let smoothing = smoothing();
+        let mut loss = aligned();
+        smooth(&mut loss, smoothing, weight(), window() as usize);
+        let reference_key = reference();
+        let reference_index = series.with_untracked(|series| {
+            series
+                .iter()
+                .position(|info| Some(info.key) == reference_key)
+        });
+        let smoothed = stats_on_smoothed() && smoothing != Smoothing::None;
+        let diff = calculate_diff(&loss, reference_index.unwrap_or(0), smoothed);
+        set_global_loss(loss);
+        set_global_diff(diff);
+        replot_range();
+    });
 
     view! {
         <PageTitle text="Draw Loss Curve"/>
@@ -265,23 +897,158 @@ pub fn DrawPage() -> impl IntoView {
             class="flex flex-col items-center p-4 border-2 border-dashed border-gray-300 rounded-lg mt-10 mb-5"
         >
             <p class="text-lg font-semibold" style=move || format!("color: {};", drag_color())>
-                "Drag and Drop CSV Files"
+                "Drag and Drop CSV, JSONL, Training Logs or TensorBoard Events"
             </p>
             <p class="text-sm font-thin" style=move || format!("color: {};", drag_color())>
                 {file_name}
             </p>
+            {move || {
+                uploads
+                    .get()
+                    .into_iter()
+                    .map(|(_, name, phase, done)| {
+                        let status = format!("{phase} {name} {:.0}%", done * 100.0);
+                        view! {
+                            <div class="flex flex-row items-center gap-2 mt-2">
+                                <progress max="1" value=done class="w-64"></progress>
+                                <span class="text-sm font-thin">{status}</span>
+                            </div>
+                        }
+                    })
+                    .collect_view()
+            }}
+
         </div>
 
+        <Stack orientation=StackOrientation::Horizontal spacing=Size::Em(3.0)>
+            <FormControl class="flex flex-row">
+                <Label class="mr-2">"Format"</Label>
+                <Select
+                    options=Format::ALL.to_vec()
+                    search_text_provider=move |format: Format| format.to_string()
+                    render_option=move |format: Format| format.to_string()
+                    selected=format
+                    set_selected=set_format
+                    class="w-48"
+                />
+            </FormControl>
+
+            <Show when=move || format() == Format::CustomRegex>
+                <FormControl class="flex flex-row">
+                    <Label class="mr-2">"Pattern"</Label>
+                    <TextInput get=pattern set=set_pattern class="w-96"/>
+                </FormControl>
+            </Show>
+
+            <Show when=move || matches!(format(), Format::Auto | Format::Json)>
+                <FormControl class="flex flex-row">
+                    <Label class="mr-2">"Step Key"</Label>
+                    <TextInput
+                        get=step_key
+                        set=set_step_key
+                        placeholder="_step"
+                        class="w-36"
+                    />
+                </FormControl>
+
+                <FormControl class="flex flex-row">
+                    <Label class="mr-2">"Metric Keys"</Label>
+                    <TextInput
+                        get=metric_keys
+                        set=set_metric_keys
+                        placeholder="train.loss, eval.loss"
+                        class="w-72"
+                    />
+                </FormControl>
+            </Show>
+        </Stack>
+
         <div
             class="container flex flex-col items-center mt-10 mb-20"
             style=move || format!("display: {};", chart_visibility())
         >
+            <div class="flex flex-col gap-2 mb-5 border border-gray-300 rounded-md p-2">
+                <For
+                    each=move || runs.with(|runs| runs.iter().map(|run| run.id).collect::<Vec<_>>())
+                    key=|id| *id
+                    children=move |id| view! { <RunRow runs id/> }
+                />
+            </div>
+
+            <Stack
+                orientation=StackOrientation::Horizontal
+                spacing=Size::Em(3.0)
+                class="mb-5"
+            >
+                <FormControl class="flex flex-row">
+                    <Label class="mr-2">"Reference"</Label>
+                    <Select
+                        options=Signal::derive(move || {
+                            series().into_iter().map(|info| Some(info.key)).collect::<Vec<_>>()
+                        })
+
+                        search_text_provider=move |key| series_name(key)
+                        render_option=move |key| series_name(key)
+                        selected=reference
+                        set_selected=move |key| reference.set(key)
+                        class="w-48"
+                    />
+                </FormControl>
+
+                <FormControl class="flex flex-row">
+                    <Label class="mr-2">"Compare"</Label>
+                    <Select
+                        options=Signal::derive(move || {
+                            let reference = reference();
+                            std::iter::once(None)
+                                .chain(
+                                    series()
+                                        .into_iter()
+                                        .filter(|info| Some(info.key) != reference)
+                                        .map(|info| Some(info.key)),
+                                )
+                                .collect::<Vec<_>>()
+                        })
+
+                        search_text_provider=move |key| series_name(key)
+                        render_option=move |key| series_name(key)
+                        selected=compare
+                        set_selected=move |key| compare.set(key)
+                        class="w-48"
+                    />
+                </FormControl>
+
+                <FormControl class="flex flex-row">
+                    <Label class="mr-2">"Join"</Label>
+                    <Select
+                        options=Join::ALL.to_vec()
+                        search_text_provider=move |join: Join| join.to_string()
+                        render_option=move |join: Join| join.to_string()
+                        selected=join
+                        set_selected=set_join
+                        class="w-36"
+                    />
+                </FormControl>
+
+                <FormControl class="flex flex-row">
+                    <Label class="mr-2">"Bad Rows"</Label>
+                    <Select
+                        options=BadRowPolicy::ALL.to_vec()
+                        search_text_provider=move |policy: BadRowPolicy| policy.to_string()
+                        render_option=move |policy: BadRowPolicy| policy.to_string()
+                        selected=bad_row_policy
+                        set_selected=set_bad_row_policy
+                        class="w-36"
+                    />
+                </FormControl>
+            </Stack>
+
             <Stack orientation=StackOrientation::Horizontal spacing=Size::Em(3.0)>
                 <FormControl class="flex flex-row">
                     <Label class="mr-2">"Start"</Label>
                     <NumberInput
-                        min=0.0
-                        max=global_len
+                        min=first_step
+                        max=last_step
                         step=1.0
                         get=start
                         set=set_start
@@ -291,7 +1058,14 @@ pub fn DrawPage() -> impl IntoView {
 
                 <FormControl class="flex flex-row">
                     <Label class="mr-2">"End"</Label>
-                    <NumberInput min=1.0 max=global_len step=1.0 get=end set=set_end class="h-10"/>
+                    <NumberInput
+                        min=first_step
+                        max=last_step
+                        step=1.0
+                        get=end
+                        set=set_end
+                        class="h-10"
+                    />
                 </FormControl>
 
                 <button
@@ -300,166 +1074,1023 @@ pub fn DrawPage() -> impl IntoView {
                 >
                     "RePlot"
                 </button>
+
+                <button
+                    on:click=move |_| reset_zoom()
+                    class="hover:bg-cyan-600 rounded-md bg-gray-400 text-white text-m font-medium pl-2 pr-3 py-2 h-10 shadow-sm"
+                >
+                    "Reset Zoom"
+                </button>
+            </Stack>
+            <P class="text-sm font-thin">"Drag over a chart to zoom in, double-click to zoom out."</P>
+
+            <Stack
+                orientation=StackOrientation::Horizontal
+                spacing=Size::Em(3.0)
+                class="mt-5"
+            >
+                <FormControl class="flex flex-row">
+                    <Label class="mr-2">"Smoothing"</Label>
+                    <Select
+                        options=Smoothing::ALL.to_vec()
+                        search_text_provider=move |smoothing: Smoothing| smoothing.to_string()
+                        render_option=move |smoothing: Smoothing| smoothing.to_string()
+                        selected=smoothing
+                        set_selected=set_smoothing
+                        class="w-48"
+                    />
+                </FormControl>
+
+                <Show when=move || smoothing() == Smoothing::Ema>
+                    <FormControl class="flex flex-row items-center">
+                        <Label class="mr-2">"Weight"</Label>
+                        <input
+                            type="range"
+                            min="0"
+                            max="0.99"
+                            step="0.01"
+                            prop:value=move || weight().to_string()
+                            on:input=move |ev| {
+                                if let Ok(value) = event_target_value(&ev).parse() {
+                                    set_weight(value);
+                                }
+                            }
+
+                            class="w-48 mr-2"
+                        />
+                        <P>{move || format!("{:.2}", weight())}</P>
+                    </FormControl>
+                </Show>
+
+                <Show when=move || {
+                    matches!(smoothing(), Smoothing::MovingAverage | Smoothing::Median)
+                }>
+                    <FormControl class="flex flex-row">
+                        <Label class="mr-2">"Window"</Label>
+                        <NumberInput
+                            min=1.0
+                            max=1000.0
+                            step=1.0
+                            get=window
+                            set=set_window
+                            class="h-10"
+                        />
+                    </FormControl>
+                </Show>
+
+                <FormControl class="flex flex-row items-center">
+                    <Checkbox checked=stats_on_smoothed set_checked=set_stats_on_smoothed/>
+                    <Label class="ml-2">"Stats on Smoothed"</Label>
+                </FormControl>
+            </Stack>
+
+            <Stack
+                orientation=StackOrientation::Horizontal
+                spacing=Size::Em(3.0)
+                class="mt-5"
+            >
+                <FormControl class="flex flex-row">
+                    <Label class="mr-2">"Loss Axis"</Label>
+                    <Select
+                        options=vec![Scale::Linear, Scale::Log]
+                        search_text_provider=move |scale: Scale| scale.to_string()
+                        render_option=move |scale: Scale| scale.to_string()
+                        selected=loss_scale
+                        set_selected=set_loss_scale
+                        class="w-48"
+                    />
+                </FormControl>
+
+                <FormControl class="flex flex-row">
+                    <Label class="mr-2">"Loss Y Min"</Label>
+                    <TextInput get=loss_y_min set=set_loss_y_min placeholder="auto" class="w-24"/>
+                </FormControl>
+
+                <FormControl class="flex flex-row">
+                    <Label class="mr-2">"Loss Y Max"</Label>
+                    <TextInput get=loss_y_max set=set_loss_y_max placeholder="auto" class="w-24"/>
+                </FormControl>
+            </Stack>
+
+            <Stack
+                orientation=StackOrientation::Horizontal
+                spacing=Size::Em(3.0)
+                class="mt-5"
+            >
+                <FormControl class="flex flex-row">
+                    <Label class="mr-2">"Diff Axis"</Label>
+                    <Select
+                        options=vec![Scale::Linear, Scale::SymLog]
+                        search_text_provider=move |scale: Scale| scale.to_string()
+                        render_option=move |scale: Scale| scale.to_string()
+                        selected=diff_scale
+                        set_selected=set_diff_scale
+                        class="w-48"
+                    />
+                </FormControl>
+
+                <Show when=move || diff_scale() == Scale::SymLog>
+                    <FormControl class="flex flex-row">
+                        <Label class="mr-2">"Linear Width"</Label>
+                        <NumberInput
+                            min=0.000001
+                            step=0.001
+                            get=linear_width
+                            set=set_linear_width
+                            class="h-10 w-32"
+                        />
+                    </FormControl>
+                </Show>
+
+                <FormControl class="flex flex-row">
+                    <Label class="mr-2">"Diff Y Min"</Label>
+                    <TextInput get=diff_y_min set=set_diff_y_min placeholder="auto" class="w-24"/>
+                </FormControl>
+
+                <FormControl class="flex flex-row">
+                    <Label class="mr-2">"Diff Y Max"</Label>
+                    <TextInput get=diff_y_max set=set_diff_y_max placeholder="auto" class="w-24"/>
+                </FormControl>
+
+                <FormControl class="flex flex-row items-center">
+                    <Checkbox checked=rel_percent set_checked=set_rel_percent/>
+                    <Label class="ml-2">"Rel Diff in %"</Label>
+                </FormControl>
             </Stack>
 
             <div class="chart-theme mt-7">
-                <Chart
-                    debug=false
-                    aspect_ratio=AspectRatio::from_env_width(400.0)
-                    left=y_ticks.clone()
-                    bottom=RotatedLabel::middle("Step")
-                    // bottom=x_ticks.clone()
-                    top=RotatedLabel::middle("Loss Curve")
-                    right=Legend::end()
-
-                    inner=[
-                        XGridLine::from_ticks(x_ticks.clone()).into_inner(),
-                        YGridLine::from_ticks(y_ticks.clone()).into_inner(),
-                        AxisMarker::left_edge().into_inner(),
-                        AxisMarker::bottom_edge().into_inner(),
-                        YGuideLine::over_mouse().into_inner(),
-                        XGuideLine::over_data().into_inner(),
-                    ]
-
-                    tooltip=Tooltip::left_cursor().show_x_ticks(true).skip_missing(true)
-                    series=loss_series
-                    data=loss
-                />
+                <div node_ref=loss_chart>
+                    <Brush cursor domain=loss_domain on_zoom=zoom on_reset=move |_| reset_zoom()>
+                        {
+                            let x_ticks = x_ticks.clone();
+                            move || {
+                                let y_ticks = scaled_ticks(loss_scale(), linear_width());
+                                view! {
+                                    <Chart
+                                        debug=false
+                                        aspect_ratio=AspectRatio::from_env_width(400.0)
+                                        left=y_ticks.clone()
+                                        bottom=RotatedLabel::middle("Step")
+                                        // bottom=x_ticks.clone()
+                                        top=RotatedLabel::middle("Loss Curve")
+                                        right=Legend::end()
+
+                                        inner=[
+                                            XGridLine::from_ticks(x_ticks.clone()).into_inner(),
+                                            YGridLine::from_ticks(y_ticks.clone()).into_inner(),
+                                            AxisMarker::left_edge().into_inner(),
+                                            AxisMarker::bottom_edge().into_inner(),
+                                            YGuideLine::over_mouse().into_inner(),
+                                            XGuideLine::over_data().into_inner(),
+                                        ]
+
+                                        tooltip=Tooltip::left_cursor().show_x_ticks(true).skip_missing(true)
+                                        series=loss_series()
+                                        data=plotted_loss
+                                    />
+                                }
+                            }
+                        }
+                    </Brush>
+                </div>
 
                 <hr class="border-t border-dotted border-gray-300 mt-5 mb-5 w-full"/>
 
-                <Chart
-                    debug=false
-                    aspect_ratio=AspectRatio::from_env_width(400.0)
-                    left=y_ticks.clone()
-                    bottom=RotatedLabel::middle("Step")
-                    // bottom=x_ticks.clone()
-                    top=RotatedLabel::middle("Loss Diff Curve")
-                    right=Legend::end()
-
-                    inner=[
-                        XGridLine::from_ticks(x_ticks.clone()).into_inner(),
-                        YGridLine::from_ticks(y_ticks.clone()).into_inner(),
-                        AxisMarker::left_edge().into_inner(),
-                        AxisMarker::bottom_edge().into_inner(),
-                        YGuideLine::over_mouse().into_inner(),
-                        XGuideLine::over_data().into_inner(),
-                    ]
-
-                    tooltip=Tooltip::left_cursor().show_x_ticks(true).skip_missing(true)
-                    series=diff_series
-                    data=diff
-                />
+                <div node_ref=diff_chart>
+                    <Brush cursor domain=diff_domain on_zoom=zoom on_reset=move |_| reset_zoom()>
+                        {
+                            let x_ticks = x_ticks.clone();
+                            move || {
+                                let y_ticks = scaled_ticks(diff_scale(), linear_width());
+                                view! {
+                                    <Chart
+                                        debug=false
+                                        aspect_ratio=AspectRatio::from_env_width(400.0)
+                                        left=y_ticks.clone()
+                                        bottom=RotatedLabel::middle("Step")
+                                        // bottom=x_ticks.clone()
+                                        top=RotatedLabel::middle("Loss Diff Curve")
+                                        right=Legend::end()
+
+                                        inner=[
+                                            XGridLine::from_ticks(x_ticks.clone()).into_inner(),
+                                            YGridLine::from_ticks(y_ticks.clone()).into_inner(),
+                                            AxisMarker::left_edge().into_inner(),
+                                            AxisMarker::bottom_edge().into_inner(),
+                                            YGuideLine::over_mouse().into_inner(),
+                                            XGuideLine::over_data().into_inner(),
+                                        ]
+
+                                        tooltip=Tooltip::left_cursor().show_x_ticks(true).skip_missing(true)
+                                        series=diff_series()
+                                        data=plotted_diff
+                                    />
+                                }
+                            }
+                        }
+                    </Brush>
+                </div>
+
+                <hr class="border-t border-dotted border-gray-300 mt-5 mb-5 w-full"/>
+
+                {
+                    let (x_ticks, y_ticks) = (x_ticks.clone(), y_ticks.clone());
+                    move || {
+                        view! {
+                            <Chart
+                                debug=false
+                                aspect_ratio=AspectRatio::from_env_width(400.0)
+                                left=y_ticks.clone()
+                                bottom=RotatedLabel::middle("Diff")
+                                top=RotatedLabel::middle("Loss Diff Histogram")
+                                right=Legend::end()
+
+                                inner=[
+                                    XGridLine::from_ticks(x_ticks.clone()).into_inner(),
+                                    YGridLine::from_ticks(y_ticks.clone()).into_inner(),
+                                    AxisMarker::left_edge().into_inner(),
+                                    AxisMarker::bottom_edge().into_inner(),
+                                    XGuideLine::over_data().into_inner(),
+                                ]
+
+                                tooltip=Tooltip::left_cursor().show_x_ticks(true)
+                                series=histogram_series()
+                                data=histogram
+                            />
+                        }
+                    }
+                }
             </div>
 
-            <div class="flex flex-row justify-center mt-5">
-                <Grid
-                    gap=Size::Em(0.5)
-                    class="border border-red-300 border-dashed rounded-md p-4 w-7/12"
+            <div class="flex flex-row flex-wrap justify-center gap-2 mt-5">
+                <button
+                    on:click=move |_| save_chart(loss_chart, "loss", "svg")
+                    class="hover:bg-cyan-600 rounded-md bg-gray-400 text-white text-sm font-medium px-2 py-1 shadow-sm"
                 >
-                    <Row>
-                        <Col xs=3>
-                            <P class="text-cyan-700">
-                                "Max Diff: "
-                                {move || {
-                                    let f = feature();
-                                    format!("{:.6} (step={})", f.max_diff.0, f.max_diff.1)
-                                }}
-
-                            </P>
-                        </Col>
-                        <Col xs=3>
-                            <P class="text-cyan-700">
-                                "Min Diff: "
-                                {move || {
-                                    let f = feature();
-                                    format!("{:.6} (step={})", f.min_diff.0, f.min_diff.1)
-                                }}
-
-                            </P>
-                        </Col>
-                    </Row>
-                    <Row>
-                        <Col xs=3>
-                            <P class="text-cyan-700">
-                                "Max Abs Diff: "
-                                {move || {
-                                    let f = feature();
-                                    format!("{:.6} (step={})", f.max_abs_diff.0, f.max_abs_diff.1)
-                                }}
-
-                            </P>
-                        </Col>
-                        <Col xs=3>
-                            <P class="text-cyan-700">
-                                "Min Abs Diff: "
-                                {move || {
-                                    let f = feature();
-                                    format!("{:.6} (step={})", f.min_abs_diff.0, f.min_abs_diff.1)
-                                }}
-
-                            </P>
-                        </Col>
-                    </Row>
-                    <Row>
-                        <Col xs=3>
-                            <P class="text-cyan-700">
-                                "Max N-Diff: "
-                                {move || {
-                                    let f = feature();
-                                    if f.max_n_diff.0 == f64::MIN {
-                                        format!("null (step=null)")
-                                    } else {
-                                        format!("{:.6} (step={})", f.max_n_diff.0, f.max_n_diff.1)
-                                    }
-                                }}
-
-                            </P>
-                        </Col>
-                        <Col xs=3>
-                            <P class="text-cyan-700">
-                                "Min P-Diff: "
-                                {move || {
-                                    let f = feature();
-                                    if f.min_p_diff.0 == f64::MAX {
-                                        format!("null (step=null)")
-                                    } else {
-                                        format!("{:.6} (step={})", f.min_p_diff.0, f.min_p_diff.1)
+                    "Loss Chart SVG"
+                </button>
+                <button
+                    on:click=move |_| save_chart(loss_chart, "loss", "png")
+                    class="hover:bg-cyan-600 rounded-md bg-gray-400 text-white text-sm font-medium px-2 py-1 shadow-sm"
+                >
+                    "Loss Chart PNG"
+                </button>
+                <button
+                    on:click=move |_| save_chart(diff_chart, "loss_diff", "svg")
+                    class="hover:bg-cyan-600 rounded-md bg-gray-400 text-white text-sm font-medium px-2 py-1 shadow-sm"
+                >
+                    "Diff Chart SVG"
+                </button>
+                <button
+                    on:click=move |_| save_chart(diff_chart, "loss_diff", "png")
+                    class="hover:bg-cyan-600 rounded-md bg-gray-400 text-white text-sm font-medium px-2 py-1 shadow-sm"
+                >
+                    "Diff Chart PNG"
+                </button>
+                <DownloadButton
+                    file_name=Signal::derive(move || export_name("loss_statistics", "csv"))
+                    mime="text/csv"
+                    content=move |_| {
+                        let reference = series_name(reference.get_untracked());
+                        features.with_untracked(|features| features_to_csv(&reference, features))
+                    }
+                >
+                    "Statistics CSV"
+                </DownloadButton>
+                <DownloadButton
+                    file_name=Signal::derive(move || export_name("loss_statistics", "json"))
+                    mime="application/json"
+                    content=move |_| {
+                        let reference = series_name(reference.get_untracked());
+                        features.with_untracked(|features| features_to_json(&reference, features))
+                    }
+                >
+                    "Statistics JSON"
+                </DownloadButton>
+                <DownloadButton
+                    file_name=Signal::derive(move || export_name("loss_diff", "csv"))
+                    mime="text/csv"
+                    content=move |_| {
+                        let reference = series_name(reference.get_untracked());
+                        diff.with_untracked(|diff| diffs_to_csv(&reference, diff, &compared()))
+                    }
+                >
+                    "Diffs CSV"
+                </DownloadButton>
+                <DownloadButton
+                    file_name=Signal::derive(move || export_name("loss_diff", "json"))
+                    mime="application/json"
+                    content=move |_| {
+                        let reference = series_name(reference.get_untracked());
+                        diff.with_untracked(|diff| diffs_to_json(&reference, diff, &compared()))
+                    }
+                >
+                    "Diffs JSON"
+                </DownloadButton>
+                <button
+                    on:click=move |_| share.dispatch(())
+                    disabled=share.pending()
+                    class="hover:bg-cyan-600 disabled:opacity-50 disabled:cursor-not-allowed rounded-md bg-red-400 text-white text-m font-medium pl-2 pr-3 py-2 shadow-sm"
+                >
+                    "Save & Share"
+                </button>
+            </div>
+            {move || {
+                share_link()
+                    .map(|link| {
+                        view! {
+                            <a href=link.clone() class="text-sm text-cyan-700 underline mt-2">
+                                {link}
+                            </a>
+                        }
+                    })
+            }}
+
+            <div class="flex flex-col items-center gap-2 mt-5 border border-gray-300 rounded-md p-2">
+                <P class="font-semibold">"Events"</P>
+                <Stack orientation=StackOrientation::Horizontal spacing=Size::Em(3.0)>
+                    <FormControl class="flex flex-row">
+                        <Label class="mr-2">"Spike Window"</Label>
+                        <NumberInput
+                            min=2.0
+                            max=10000.0
+                            step=1.0
+                            get=spike_window
+                            set=set_spike_window
+                            class="h-10 w-32"
+                        />
+                    </FormControl>
+
+                    <FormControl class="flex flex-row">
+                        <Label class="mr-2">"Spike Z-Score"</Label>
+                        <NumberInput
+                            min=0.0
+                            max=100.0
+                            step=0.5
+                            get=spike_z_score
+                            set=set_spike_z_score
+                            class="h-10 w-32"
+                        />
+                    </FormControl>
+
+                    <FormControl class="flex flex-row">
+                        <Label class="mr-2">"Divergence Rel Diff"</Label>
+                        <NumberInput
+                            min=0.0
+                            max=100.0
+                            step=0.01
+                            get=divergence
+                            set=set_divergence
+                            class="h-10 w-32"
+                        />
+                    </FormControl>
+
+                    <FormControl class="flex flex-row">
+                        <Label class="mr-2">"Divergence Steps"</Label>
+                        <NumberInput
+                            min=1.0
+                            max=10000.0
+                            step=1.0
+                            get=divergence_steps
+                            set=set_divergence_steps
+                            class="h-10 w-32"
+                        />
+                    </FormControl>
+                </Stack>
+                {move || {
+                    const SHOWN: usize = 100;
+                    events
+                        .with(|events| {
+                            let more = events.len().saturating_sub(SHOWN);
+                            let empty = events.is_empty();
+                            let list = events
+                                .iter()
+                                .take(SHOWN)
+                                .map(|event| {
+                                    let step = event.step;
+                                    view! {
+                                        <button
+                                            on:click=move |_| zoom_to(step)
+                                            class="text-left text-sm hover:bg-gray-100 rounded-md px-2"
+                                        >
+                                            {format!(
+                                                "{} in {} at step {}: {}",
+                                                event.kind,
+                                                event.series,
+                                                event.step,
+                                                event.detail,
+                                            )}
+                                        </button>
                                     }
-                                }}
-
-                            </P>
-                        </Col>
-                    </Row>
-                    <Row>
-                        <Col xs=3>
-                            <P class="text-cyan-700">
-                                "Mean Diff: " {move || { format!("{:.6}", feature().mean_diff) }}
-                            </P>
-                        </Col>
-                        <Col xs=3>
-                            <P class="text-cyan-700">
-                                "Mean Abs Diff: "
-                                {move || { format!("{:.6}", feature().mean_abs_diff) }}
-                            </P>
-                        </Col>
-                    </Row>
-                    <Row>
-                        <Col xs=3>
-                            <P class="text-cyan-700">
-                                "Mean Rel Diff: "
-                                {move || { format!("{:.6}", feature().mean_rel_diff) }}
-                            </P>
-                        </Col>
-                        <Col xs=3>
-                            <P class="text-cyan-700">
-                                "Mean Abs Rel Diff: "
-                                {move || { format!("{:.6}", feature().mean_abs_rel_diff) }}
-                            </P>
-                        </Col>
-                    </Row>
-                </Grid>
+                                })
+                                .collect_view();
+                            view! {
+                                {list}
+                                <Show when=move || { more > 0 }>
+                                    <P>{format!("and {more} more")}</P>
+                                </Show>
+                                <Show when=move || { more == 0 && empty }>
+                                    <P>"No events detected"</P>
+                                </Show>
+                            }
+                        })
+                }}
+
+            </div>
+
+            <div class="flex flex-col items-center gap-2 mt-5 border border-gray-300 rounded-md p-2">
+                <P class="font-semibold">"Tolerances"</P>
+                <For
+                    each=move || rules.with(|rules| rules.iter().map(|rule| rule.id).collect::<Vec<_>>())
+                    key=|id| *id
+                    children=move |id| view! { <RuleRow rules id/> }
+                />
+                <button
+                    on:click=move |_| {
+                        rules.update(|rules| rules.push(Rule::new(Metric::MaxAbs, 0.1, 0.0)))
+                    }
+
+                    class="hover:bg-cyan-600 rounded-md bg-gray-400 text-white text-sm font-medium px-2 py-1 shadow-sm"
+                >
+                    "Add Rule"
+                </button>
+            </div>
+
+            <P class="text-2xl font-bold mt-5">
+                {move || match passed() {
+                    true => view! { <span class="text-green-600">"PASS"</span> },
+                    false => view! { <span class="text-red-600">"FAIL"</span> },
+                }}
+
+            </P>
+            {move || {
+                let reference = series_name(reference());
+                verdicts()
+                    .into_iter()
+                    .map(|(name, checks)| {
+                        view! { <VerdictList title=format!("{name} vs {reference}") checks/> }
+                    })
+                    .collect_view()
+            }}
+
+            {move || {
+                let reference = series_name(reference());
+                features()
+                    .into_iter()
+                    .map(|(name, feature)| {
+                        view! { <FeatureGrid title=format!("{name} vs {reference}") feature=feature/> }
+                    })
+                    .collect_view()
+            }}
+
+            <div class="flex flex-col items-center gap-2 mt-5 border border-gray-300 rounded-md p-2">
+                <P class="font-semibold">"Statistics over Time"</P>
+                <Stack orientation=StackOrientation::Horizontal spacing=Size::Em(3.0)>
+                    <FormControl class="flex flex-row">
+                        <Label class="mr-2">"Split By"</Label>
+                        <Select
+                            options=Segmentation::ALL.to_vec()
+                            search_text_provider=move |segmentation: Segmentation| {
+                                segmentation.to_string()
+                            }
+
+                            render_option=move |segmentation: Segmentation| segmentation.to_string()
+                            selected=segmentation
+                            set_selected=set_segmentation
+                            class="w-48"
+                        />
+                    </FormControl>
+
+                    <Show when=move || segmentation() == Segmentation::Windows>
+                        <FormControl class="flex flex-row">
+                            <Label class="mr-2">"Window Steps"</Label>
+                            <NumberInput
+                                min=1.0
+                                step=1.0
+                                get=segment_size
+                                set=set_segment_size
+                                class="h-10 w-32"
+                            />
+                        </FormControl>
+                    </Show>
+
+                    <Show when=move || segmentation() == Segmentation::Phases>
+                        <FormControl class="flex flex-row">
+                            <Label class="mr-2">"Phases"</Label>
+                            <TextInput
+                                get=phases
+                                set=set_phases
+                                placeholder="warmup: 0-1000, decay: 1000-"
+                                class="w-96"
+                            />
+                        </FormControl>
+                    </Show>
+                </Stack>
+                {move || {
+                    segments()
+                        .err()
+                        .map(|error| view! { <P class="text-red-600">{error}</P> })
+                }}
+
+                {move || {
+                    let reference = series_name(reference());
+                    segment_tables()
+                        .into_iter()
+                        .map(|(name, segments)| {
+                            view! { <SegmentTable title=format!("{name} vs {reference}") segments/> }
+                        })
+                        .collect_view()
+                }}
+
             </div>
+
+        </div>
+    }
+}
+
+/// The hovered step and the range being brushed, shared by the charts so that both highlight
+/// the same steps.
+#[derive(Copy, Clone)]
+struct ChartCursor {
+    hover: RwSignal<Option<f64>>,
+    /// Where the drag started and where it is now.
+    brush: RwSignal<Option<(f64, f64)>>,
+}
+
+/// Zooms the wrapped chart to the steps dragged over and draws the shared cursor over it.
+#[component]
+fn Brush(
+    cursor: ChartCursor,
+    /// The first and last step of the plotted rows.
+    #[prop(into)]
+    domain: Signal<(f64, f64)>,
+    #[prop(into)] on_zoom: Callback<(f64, f64)>,
+    #[prop(into)] on_reset: Callback<()>,
+    children: Children,
+) -> impl IntoView {
+    let container = create_node_ref::<Div>();
+
+    // client x of the container and of the plot area, and the plot area width. The plot area is
+    // spanned by the bottom axis marker, the ticks and the legend take up the rest.
+    let plot_area = move || {
+        let container = container.get_untracked()?;
+        let bounds = container.get_bounding_client_rect();
+        let markers = container
+            .query_selector_all("._chartistry_axis_marker")
+            .ok()?;
+        let (left, width) = (0..markers.length())
+            .filter_map(|i| markers.item(i)?.dyn_into::<web_sys::Element>().ok())
+            .map(|marker| {
+                let area = marker.get_bounding_client_rect();
+                (area.left(), area.width())
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .filter(|(_, width)| *width > 0.0)
+            .unwrap_or((bounds.left(), bounds.width()));
+        Some((bounds.left(), left, width))
+    };
+    let to_step = move |client_x: f64| {
+        let (_, left, width) = plot_area()?;
+        let (first, last) = domain.get_untracked();
+        Some(first + ((client_x - left) / width).clamp(0.0, 1.0) * (last - first))
+    };
+    // position of a step within the container
+    let to_x = move |step: f64| {
+        let (origin, left, width) = plot_area()?;
+        let (first, last) = domain.get();
+        let ratio = match last > first {
+            true => ((step - first) / (last - first)).clamp(0.0, 1.0),
+            false => 0.0,
+        };
+        Some(left - origin + ratio * width)
+    };
+
+    let on_mousedown = move |ev: ev::MouseEvent| {
+        if ev.button() != 0 {
+            return;
+        }
+        // keep the drag from selecting text
+        ev.prevent_default();
+        cursor
+            .brush
+            .set(to_step(ev.client_x() as f64).map(|step| (step, step)));
+    };
+    let on_mousemove = move |ev: ev::MouseEvent| {
+        let step = to_step(ev.client_x() as f64);
+        cursor.hover.set(step);
+        if let (Some(step), Some((from, _))) = (step, cursor.brush.get_untracked()) {
+            cursor.brush.set(Some((from, step)));
+        }
+    };
+    let on_mouseup = move |_| {
+        let Some((from, to)) = cursor.brush.get_untracked() else {
+            return;
+        };
+        cursor.brush.set(None);
+        // a click is not a drag
+        let dragged = match (to_x(from), to_x(to)) {
+            (Some(from), Some(to)) => (from - to).abs() > 4.0,
+            _ => false,
+        };
+        if dragged {
+            on_zoom.call((from.min(to), from.max(to)));
+        }
+    };
+    let on_mouseleave = move |_| {
+        cursor.hover.set(None);
+        cursor.brush.set(None);
+    };
+
+    view! {
+        <div
+            node_ref=container
+            class="relative select-none"
+            on:mousedown=on_mousedown
+            on:mousemove=on_mousemove
+            on:mouseup=on_mouseup
+            on:mouseleave=on_mouseleave
+            on:dblclick=move |_| on_reset.call(())
+        >
+            {children()}
+            {move || {
+                let (from, to) = cursor.brush.get()?;
+                let (from, to) = (to_x(from.min(to))?, to_x(from.max(to))?);
+                Some(
+                    view! {
+                        <div
+                            class="absolute top-0 bottom-0 bg-cyan-500 opacity-20 pointer-events-none"
+                            style=format!("left: {from}px; width: {}px;", to - from)
+                        ></div>
+                    },
+                )
+            }}
+
+            {move || {
+                let step = cursor.hover.get()?;
+                let x = to_x(step)?;
+                Some(
+                    view! {
+                        <div
+                            class="absolute top-0 bottom-0 border-l border-dashed border-gray-500 pointer-events-none"
+                            style=format!("left: {x}px;")
+                        >
+                            <span class="text-xs text-gray-500 ml-1">{format!("{step:.0}")}</span>
+                        </div>
+                    },
+                )
+            }}
+
+        </div>
+    }
+}
+
+/// Name, colour, visibility and column mapping of one run.
+#[component]
+fn RunRow(runs: RwSignal<Vec<Run>>, id: Uuid) -> impl IntoView {
+    let update = move |f: &dyn Fn(&mut Run)| {
+        runs.update(|runs| {
+            if let Some(run) = runs.iter_mut().find(|r| r.id == id) {
+                f(run);
+            }
+        })
+    };
+    let columns =
+        move || runs.with(|runs| find_run(runs, id).map_or(0, |run| run.table.headers.len()));
+    let column_name = move |column: usize| {
+        runs.with(|runs| find_run(runs, id).map(|run| run.table.header(column)))
+            .unwrap_or_default()
+    };
+    let step = move || runs.with(|runs| find_run(runs, id).map_or(0, |run| run.mapping.step));
+    let toggle_series = move |column: usize, checked: bool| {
+        update(&|run| {
+            run.mapping.series.retain(|&c| c != column);
+            if checked {
+                run.mapping.series.push(column);
+                run.mapping.series.sort_unstable();
+            }
+        })
+    };
+
+    view! {
+        <div class="flex flex-row flex-wrap gap-2 items-center">
+            <input
+                type="color"
+                prop:value=move || {
+                    runs.with(|runs| find_run(runs, id).map(|run| run.colour.clone())).unwrap_or_default()
+                }
+                on:input=move |ev| {
+                    let colour = event_target_value(&ev);
+                    update(&|run| run.colour = colour.clone());
+                }
+
+                class="w-8 h-8"
+            />
+            <TextInput
+                get=Signal::derive(move || {
+                    runs.with(|runs| find_run(runs, id).map(|run| run.name.clone())).unwrap_or_default()
+                })
+                set=move |name: String| update(&|run| run.name = name.clone())
+                class="w-48"
+            />
+            <Label>"Step"</Label>
+            <Select
+                options=Signal::derive(move || (0..columns()).collect::<Vec<_>>())
+                search_text_provider=move |column| column_name(column)
+                render_option=move |column| column_name(column)
+                selected=Signal::derive(step)
+                set_selected=move |column| update(&|run| run.mapping.step = column)
+                class="w-36"
+            />
+            <Label>"Series"</Label>
+            {move || {
+                (0..columns())
+                    .filter(|&column| column != step())
+                    .map(|column| {
+                        view! {
+                            <Checkbox
+                                checked=Signal::derive(move || {
+                                    runs.with(|runs| {
+                                        find_run(runs, id).is_some_and(|run| run.mapping.series.contains(&column))
+                                    })
+                                })
+
+                                set_checked=move |checked| toggle_series(column, checked)
+                            />
+                            <Label class="mr-2">{column_name(column)}</Label>
+                        }
+                    })
+                    .collect_view()
+            }}
+
+            <Checkbox
+                checked=Signal::derive(move || {
+                    runs.with(|runs| find_run(runs, id).is_some_and(|run| run.visible))
+                })
+                set_checked=move |visible| update(&|run| run.visible = visible)
+            />
+            <Label>"Visible"</Label>
+            <button
+                on:click=move |_| runs.update(|runs| runs.retain(|r| r.id != id))
+                class="hover:bg-cyan-600 rounded-md bg-gray-400 text-white text-sm font-medium px-2 py-1 shadow-sm"
+            >
+                "Remove"
+            </button>
+        </div>
+    }
+}
+
+/// Metric, threshold and warmup of one tolerance rule.
+#[component]
+fn RuleRow(rules: RwSignal<Vec<Rule>>, id: Uuid) -> impl IntoView {
+    let update = move |f: &dyn Fn(&mut Rule)| {
+        rules.update(|rules| {
+            if let Some(rule) = rules.iter_mut().find(|r| r.id == id) {
+                f(rule);
+            }
+        })
+    };
+    let get = move |f: fn(&Rule) -> f64| {
+        Signal::derive(move || rules.with(|rules| rules.iter().find(|r| r.id == id).map_or(0.0, f)))
+    };
+
+    view! {
+        <div class="flex flex-row flex-wrap gap-2 items-center">
+            <Select
+                options=Metric::ALL.to_vec()
+                search_text_provider=move |metric: Metric| metric.to_string()
+                render_option=move |metric: Metric| metric.to_string()
+                selected=Signal::derive(move || {
+                    rules
+                        .with(|rules| rules.iter().find(|r| r.id == id).map(|rule| rule.metric))
+                        .unwrap_or(Metric::MaxAbs)
+                })
+
+                set_selected=move |metric| update(&|rule| rule.metric = metric)
+                class="w-48"
+            />
+            <Label>"Below"</Label>
+            <NumberInput
+                min=0.0
+                step=0.001
+                get=get(|rule| rule.threshold)
+                set=move |threshold| update(&|rule| rule.threshold = threshold)
+                class="h-10 w-32"
+            />
+            <Label>"From Step"</Label>
+            <NumberInput
+                min=0.0
+                step=1.0
+                get=get(|rule| rule.warmup)
+                set=move |warmup| update(&|rule| rule.warmup = warmup)
+                class="h-10 w-32"
+            />
+            <button
+                on:click=move |_| rules.update(|rules| rules.retain(|r| r.id != id))
+                class="hover:bg-cyan-600 rounded-md bg-gray-400 text-white text-sm font-medium px-2 py-1 shadow-sm"
+            >
+                "Remove"
+            </button>
+        </div>
+    }
+}
+
+/// Outcome of every tolerance rule for one diff series.
+#[component]
+fn VerdictList(title: String, checks: Vec<(Rule, Check)>) -> impl IntoView {
+    view! {
+        <div class="flex flex-col items-center mt-3">
+            <P class="font-semibold">{title}</P>
+            {checks
+                .into_iter()
+                .map(|(rule, check)| {
+                    let outcome = match (check.passed, check.first_violation) {
+                        (true, _) => "PASS".to_string(),
+                        (false, Some(step)) => format!("FAIL, first violated at step {step}"),
+                        (false, None) => "FAIL, no steps checked".to_string(),
+                    };
+                    let class = match check.passed {
+                        true => "text-green-600",
+                        false => "text-red-600",
+                    };
+                    view! { <P class=class>{format!("{rule}: {:.6} {outcome}", check.value)}</P> }
+                })
+                .collect_view()}
+        </div>
+    }
+}
+
+/// Statistics of one diff series per window or phase, those exceeding a tolerance in red.
+#[component]
+fn SegmentTable(title: String, segments: Vec<SegmentFeature>) -> impl IntoView {
+    view! {
+        <div class="flex flex-col items-center mt-3 w-full">
+            <P class="font-semibold">{title}</P>
+            <table class="w-full text-left text-cyan-700">
+                <thead>
+                    <tr>
+                        <th>"Steps"</th>
+                        <th>"Mean Diff"</th>
+                        <th>"Mean Abs Diff"</th>
+                        <th>"Max Abs Diff"</th>
+                        <th>"Mean Abs Rel Diff"</th>
+                        <th>"Std Diff"</th>
+                        <th>"P99 Abs Rel Diff"</th>
+                        <th>"NaN"</th>
+                        <th>"Inf"</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {segments
+                        .into_iter()
+                        .map(|SegmentFeature { segment, feature: f, exceeded }| {
+                            let class = match exceeded {
+                                true => "text-red-600 bg-red-50",
+                                false => "",
+                            };
+                            let max_abs_diff = f
+                                .max_abs_diff
+                                .map_or_else(|| "-".to_string(), |(value, _)| format!("{value:.6}"));
+                            view! {
+                                <tr class=class>
+                                    <td>{segment.name}</td>
+                                    <td>{format!("{:.6}", f.mean_diff)}</td>
+                                    <td>{format!("{:.6}", f.mean_abs_diff)}</td>
+                                    <td>{max_abs_diff}</td>
+                                    <td>{format!("{:.6}", f.mean_abs_rel_diff)}</td>
+                                    <td>{format!("{:.6}", f.std_diff)}</td>
+                                    <td>{format!("{:.6}", f.abs_rel_diff_percentiles.p99)}</td>
+                                    <td>{f.nan.len()}</td>
+                                    <td>{f.inf.len()}</td>
+                                </tr>
+                            }
+                        })
+                        .collect_view()}
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+/// Statistics of one diff series.
+#[component]
+fn FeatureGrid(title: String, feature: Feature) -> impl IntoView {
+    let f = &feature;
+    let at_step = |value: Option<(f64, usize)>| match value {
+        Some((value, step)) => format!("{value:.6} (step={step})"),
+        None => "null (step=null)".to_string(),
+    };
+    view! {
+        <div class="flex flex-col items-center mt-5">
+            <P class="font-semibold">{title}</P>
+            <Grid gap=Size::Em(0.5) class="border border-red-300 border-dashed rounded-md p-4 w-7/12">
+                <Row>
+                    <Col xs=3>
+                        <P class="text-cyan-700">
+                            "Max Diff: " {at_step(f.max_diff)}
+                        </P>
+                    </Col>
+                    <Col xs=3>
+                        <P class="text-cyan-700">
+                            "Min Diff: " {at_step(f.min_diff)}
+                        </P>
+                    </Col>
+                </Row>
+                <Row>
+                    <Col xs=3>
+                        <P class="text-cyan-700">
+                            "Max Abs Diff: " {at_step(f.max_abs_diff)}
+                        </P>
+                    </Col>
+                    <Col xs=3>
+                        <P class="text-cyan-700">
+                            "Min Abs Diff: " {at_step(f.min_abs_diff)}
+                        </P>
+                    </Col>
+                </Row>
+                <Row>
+                    <Col xs=3>
+                        <P class="text-cyan-700">
+                            "Max N-Diff: " {at_step(f.max_n_diff)}
+                        </P>
+                    </Col>
+                    <Col xs=3>
+                        <P class="text-cyan-700">
+                            "Min P-Diff: " {at_step(f.min_p_diff)}
+                        </P>
+                    </Col>
+                </Row>
+                <Row>
+                    <Col xs=3>
+                        <P class="text-cyan-700">"Mean Diff: " {format!("{:.6}", f.mean_diff)}</P>
+                    </Col>
+                    <Col xs=3>
+                        <P class="text-cyan-700">
+                            "Mean Abs Diff: " {format!("{:.6}", f.mean_abs_diff)}
+                        </P>
+                    </Col>
+                </Row>
+                <Row>
+                    <Col xs=3>
+                        <P class="text-cyan-700">
+                            "Mean Rel Diff: " {format!("{:.6}", f.mean_rel_diff)}
+                        </P>
+                    </Col>
+                    <Col xs=3>
+                        <P class="text-cyan-700">
+                            "Mean Abs Rel Diff: " {format!("{:.6}", f.mean_abs_rel_diff)}
+                        </P>
+                    </Col>
+                </Row>
+                <Row>
+                    <Col xs=3>
+                        <P class="text-cyan-700">"Std Diff: " {format!("{:.6}", f.std_diff)}</P>
+                    </Col>
+                    <Col xs=3>
+                        <P class="text-cyan-700">"RMSE: " {format!("{:.6}", f.rmse)}</P>
+                    </Col>
+                </Row>
+                <Row>
+                    <Col xs=3>
+                        <P class="text-cyan-700">
+                            "Abs Diff P50/P90/P99: "
+                            {format!(
+                                "{:.6} / {:.6} / {:.6}",
+                                f.abs_diff_percentiles.p50,
+                                f.abs_diff_percentiles.p90,
+                                f.abs_diff_percentiles.p99,
+                            )}
+                        </P>
+                    </Col>
+                    <Col xs=3>
+                        <P class="text-cyan-700">
+                            "Abs Rel Diff P50/P90/P99: "
+                            {format!(
+                                "{:.6} / {:.6} / {:.6}",
+                                f.abs_rel_diff_percentiles.p50,
+                                f.abs_rel_diff_percentiles.p90,
+                                f.abs_rel_diff_percentiles.p99,
+                            )}
+                        </P>
+                    </Col>
+                </Row>
+                <Row>
+                    <Col xs=3>
+                        <P class="text-cyan-700">"Pearson: " {format!("{:.6}", f.pearson)}</P>
+                    </Col>
+                    <Col xs=3>
+                        <P class=match f.nan.is_empty() {
+                            true => "text-cyan-700",
+                            false => "text-red-600",
+                        }>"NaN: " {f.nan_summary()}</P>
+                    </Col>
+                    <Col xs=3>
+                        <P class=match f.inf.is_empty() {
+                            true => "text-cyan-700",
+                            false => "text-red-600",
+                        }>"Inf: " {f.inf_summary()}</P>
+                    </Col>
+                </Row>
+            </Grid>
         </div>
     }
 }
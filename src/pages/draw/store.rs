@@ -0,0 +1,203 @@
+use crate::loss::run::{Join, Run, SeriesKey};
+use crate::loss::scale::Scale;
+use crate::loss::segments::Segmentation;
+use crate::loss::smoothing::Smoothing;
+use crate::loss::table::{BadRowPolicy, ColumnMapping, Table};
+use crate::loss::tolerance::Rule;
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use server_fn::codec::Json;
+use uuid::Uuid;
+
+/// Largest upload of a run to store, in bytes of JSON.
+pub const MAX_RUN_BYTES: usize = 64 * 1024 * 1024;
+
+/// An uploaded run as stored on the server.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredRun {
+    /// The id the run is stored under, set by the server, the local id of the run until then.
+    pub id: Uuid,
+    /// The name the run was uploaded with.
+    pub name: String,
+    pub rows: usize,
+    /// When the run was stored, RFC 3339, set by the server.
+    pub stored_at: String,
+    pub table: Table,
+}
+
+impl StoredRun {
+    pub fn new(run: &Run) -> Self {
+        Self {
+            id: run.id,
+            name: run.name.clone(),
+            rows: run.table.rows.len(),
+            stored_at: String::new(),
+            table: run.table.clone(),
+        }
+    }
+}
+
+/// How a stored run is shown in a comparison.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunView {
+    pub id: Uuid,
+    pub name: String,
+    pub colour: String,
+    pub visible: bool,
+    pub mapping: ColumnMapping,
+}
+
+impl RunView {
+    pub fn new(run: &Run) -> Self {
+        Self {
+            id: run.id,
+            name: run.name.clone(),
+            colour: run.colour.clone(),
+            visible: run.visible,
+            mapping: run.mapping.clone(),
+        }
+    }
+
+    pub fn into_run(self, table: Table) -> Run {
+        Run {
+            id: self.id,
+            name: self.name,
+            colour: self.colour,
+            visible: self.visible,
+            table,
+            mapping: self.mapping,
+        }
+    }
+}
+
+/// Everything needed to restore the draw page: the runs, the range and the settings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Comparison {
+    pub runs: Vec<RunView>,
+    pub reference: Option<SeriesKey>,
+    pub compare: Option<SeriesKey>,
+    pub bad_row_policy: BadRowPolicy,
+    pub join: Join,
+    pub start: f64,
+    pub end: f64,
+    pub smoothing: Smoothing,
+    pub weight: f64,
+    pub window: f64,
+    pub stats_on_smoothed: bool,
+    pub rules: Vec<Rule>,
+    pub spike_window: f64,
+    pub spike_z_score: f64,
+    pub divergence: f64,
+    pub divergence_steps: f64,
+    pub segmentation: Segmentation,
+    pub segment_size: f64,
+    pub phases: String,
+    pub loss_scale: Scale,
+    pub diff_scale: Scale,
+    pub linear_width: f64,
+    pub rel_percent: bool,
+    /// Manual y limits of the loss chart as typed, empty for automatic.
+    pub loss_y_limits: (String, String),
+    /// Manual y limits of the diff chart as typed, empty for automatic.
+    pub diff_y_limits: (String, String),
+}
+
+/// `static/<kind>` under the site root, created when missing.
+#[cfg(feature = "ssr")]
+fn store_dir(kind: &str) -> Result<std::path::PathBuf, ServerFnError> {
+    let site_root =
+        std::env::var("LEPTOS_SITE_ROOT").map_err(|e| ServerFnError::new(e.to_string()))?;
+    let dir = std::path::Path::new(&site_root).join("static").join(kind);
+    std::fs::create_dir_all(&dir).map_err(|e| ServerFnError::new(e.to_string()))?;
+    Ok(dir)
+}
+
+/// Writes a file that must not exist yet, stored files are never replaced.
+#[cfg(feature = "ssr")]
+fn write_new(path: &std::path::Path, bytes: &[u8]) -> Result<(), ServerFnError> {
+    use std::io::Write;
+
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(bytes))
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Stores an uploaded run as `static/runs/<id>.json` under the site root, returning the id the
+/// server picked for it.
+#[server(input = Json)]
+pub async fn save_run(run: StoredRun) -> Result<Uuid, ServerFnError> {
+    use time::format_description::well_known::Rfc3339;
+
+    let stored_at = time::OffsetDateTime::now_utc()
+        .format(&Rfc3339)
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let run = StoredRun {
+        id: Uuid::new_v4(),
+        rows: run.table.rows.len(),
+        stored_at,
+        ..run
+    };
+    let json = serde_json::to_vec(&run).map_err(|e| ServerFnError::new(e.to_string()))?;
+    if json.len() > MAX_RUN_BYTES {
+        return Err(ServerFnError::new(format!(
+            "run {} is larger than {} MiB",
+            run.name,
+            MAX_RUN_BYTES / 1024 / 1024
+        )));
+    }
+    write_new(&store_dir("runs")?.join(format!("{}.json", run.id)), &json)?;
+    tracing::info!("Save run {} with {} rows.", run.id, run.rows);
+
+    Ok(run.id)
+}
+
+/// Stores a comparison of stored runs as `static/comparisons/<id>.json` under the site root,
+/// returning its id.
+#[server(input = Json)]
+pub async fn save_comparison(comparison: Comparison) -> Result<Uuid, ServerFnError> {
+    let runs = store_dir("runs")?;
+    if let Some(run) = comparison
+        .runs
+        .iter()
+        .find(|run| !runs.join(format!("{}.json", run.id)).exists())
+    {
+        return Err(ServerFnError::new(format!(
+            "run {} is not stored",
+            run.name
+        )));
+    }
+
+    let id = Uuid::new_v4();
+    let json = serde_json::to_vec(&comparison).map_err(|e| ServerFnError::new(e.to_string()))?;
+    write_new(&store_dir("comparisons")?.join(format!("{id}.json")), &json)?;
+    tracing::info!("Save comparison {id} of {} run(s).", comparison.runs.len());
+
+    Ok(id)
+}
+
+/// A stored comparison together with the stored runs it shows.
+#[server(input = Json)]
+pub async fn load_comparison(id: Uuid) -> Result<(Comparison, Vec<StoredRun>), ServerFnError> {
+    let read = |path: std::path::PathBuf| {
+        std::fs::read(path).map_err(|e| ServerFnError::new(e.to_string()))
+    };
+    let json = read(store_dir("comparisons")?.join(format!("{id}.json")))?;
+    let comparison = serde_json::from_slice::<Comparison>(&json)
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let dir = store_dir("runs")?;
+    let runs = comparison
+        .runs
+        .iter()
+        .map(|run| {
+            let json = read(dir.join(format!("{}.json", run.id)))?;
+            serde_json::from_slice::<StoredRun>(&json)
+                .map_err(|e| ServerFnError::new(e.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((comparison, runs))
+}